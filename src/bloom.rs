@@ -0,0 +1,94 @@
+//! Bloom post-processing for the Canvas2D renderer: bright pixels are
+//! extracted, blurred at several mip levels and added back onto the frame.
+//! There is no pixel-shader backend, so the bright pass and blur are done
+//! with the context's CSS `filter`, which browsers run on the GPU.
+
+use crate::canvas::{context_2d, create_canvas};
+use serde::{Deserialize, Serialize};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BloomConfig {
+    /// Brightness (0.0..1.0) below which pixels don't bloom.
+    pub threshold: f64,
+    pub intensity: f64,
+    /// Number of mip levels, each half the size of the previous one.
+    pub levels: u32,
+    /// Blur radius in pixels of each level.
+    pub radius: f64,
+}
+
+impl Default for BloomConfig {
+    fn default() -> Self {
+        BloomConfig {
+            threshold: 0.6,
+            intensity: 0.8,
+            levels: 4,
+            radius: 3.0,
+        }
+    }
+}
+
+pub struct Bloom {
+    config: BloomConfig,
+    width: f64,
+    height: f64,
+    // canvas, context, width, height
+    levels: Vec<(HtmlCanvasElement, CanvasRenderingContext2d, f64, f64)>,
+}
+
+impl Bloom {
+    pub fn new(config: BloomConfig, width: f64, height: f64) -> Self {
+        // Start at half resolution; the blur hides the difference
+        let levels = (1..=config.levels.max(1))
+            .map(|level| {
+                let scale = 0.5f64.powi(level as i32);
+                let (w, h) = ((width * scale).max(1.0).round(), (height * scale).max(1.0).round());
+                let canvas = create_canvas(w as u32, h as u32);
+                let context = context_2d(&canvas);
+                (canvas, context, w, h)
+            })
+            .collect();
+
+        Bloom {
+            config,
+            width,
+            height,
+            levels,
+        }
+    }
+
+    /// Blooms what has been drawn on `canvas` so far.
+    pub fn apply(&self, canvas: &HtmlCanvasElement, context: &CanvasRenderingContext2d) {
+        // Bright pass: scale brightness so the threshold lands on contrast's
+        // cut-off, 0.375, then contrast(4) clips everything below it to black
+        let threshold = self.config.threshold.clamp(0.05, 0.95);
+        let bright_pass = format!("brightness({}) contrast(4)", 0.375 / threshold);
+        let blur = format!("blur({}px)", self.config.radius);
+
+        for (i, (_, level, w, h)) in self.levels.iter().enumerate() {
+            level.clear_rect(0.0, 0.0, *w, *h);
+            if i == 0 {
+                level.set_filter(&format!("{} {}", bright_pass, blur));
+                level.draw_image_with_html_canvas_element_and_dw_and_dh(canvas, 0.0, 0.0, *w, *h).unwrap();
+            } else {
+                // Each level blurs the previous one at half the size, so the
+                // same radius reaches twice as far
+                level.set_filter(&blur);
+                let previous = &self.levels[i - 1].0;
+                level.draw_image_with_html_canvas_element_and_dw_and_dh(previous, 0.0, 0.0, *w, *h).unwrap();
+            }
+        }
+
+        context.set_global_composite_operation("lighter").unwrap();
+        context.set_global_alpha(self.config.intensity.clamp(0.0, 1.0));
+        for (level, _, _, _) in &self.levels {
+            context
+                .draw_image_with_html_canvas_element_and_dw_and_dh(level, 0.0, 0.0, self.width, self.height)
+                .unwrap();
+        }
+        context.set_global_alpha(1.0);
+        context.set_global_composite_operation("source-over").unwrap();
+    }
+}
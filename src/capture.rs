@@ -0,0 +1,324 @@
+//! Recording a `Starfield` to a file: WebM through the browser's
+//! `MediaRecorder`, or an animated GIF encoded here from the rendered frames.
+//! While recording, the animation advances by a fixed step per frame, so the
+//! result plays smoothly whatever the frame rate was during capture.
+//!
+//! Screenshots redraw the current frame at any resolution and save it as PNG.
+
+use crate::canvas::{context_2d, create_canvas, download};
+use gif::{Encoder, Frame, Repeat};
+use js_sys::{Array, Uint8Array};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    Blob, BlobEvent, BlobPropertyBag, CanvasCaptureMediaStreamTrack, CanvasRenderingContext2d, HtmlCanvasElement,
+    MediaRecorder, MediaRecorderOptions,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureFormat {
+    WebM,
+    Gif,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaptureConfig {
+    pub format: CaptureFormat,
+    /// Length in seconds.
+    pub seconds: f64,
+    pub fps: u32,
+    /// GIF size relative to the canvas; GIFs get large quickly. WebM is
+    /// always recorded at the canvas size.
+    pub gif_scale: f64,
+    /// Name of the downloaded file, without the extension.
+    pub file_name: String,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        CaptureConfig {
+            format: CaptureFormat::WebM,
+            seconds: 5.0,
+            fps: 30,
+            gif_scale: 0.5,
+            file_name: "hyperspace".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreenshotConfig {
+    /// Image size in pixels, independent of the window. A different aspect
+    /// ratio crops the view, as CSS `object-fit: cover` would.
+    pub width: u32,
+    pub height: u32,
+    /// Name of the downloaded file, without the extension.
+    pub file_name: String,
+}
+
+impl Default for ScreenshotConfig {
+    fn default() -> Self {
+        ScreenshotConfig {
+            width: 3840,
+            height: 2160,
+            file_name: "hyperspace".to_string(),
+        }
+    }
+}
+
+// Largest canvas side browsers reliably allocate
+const MAX_SIDE: u32 = 16384;
+
+#[derive(Default)]
+struct CaptureState {
+    requested: Option<CaptureConfig>,
+    recording: bool,
+    screenshot: Option<ScreenshotConfig>,
+}
+
+/// Starts recordings and screenshots of a `Starfield`: pass it as the
+/// `capture` prop and call `start` or `screenshot`, e.g. from a button. The
+/// file downloads when it's ready.
+#[derive(Clone, Default)]
+pub struct Capture(Rc<RefCell<CaptureState>>);
+
+impl PartialEq for Capture {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Capture {
+    pub fn new() -> Self {
+        Capture::default()
+    }
+
+    /// Records from the next frame on. Ignored while a recording runs.
+    pub fn start(&self, config: CaptureConfig) {
+        if !self.is_recording() {
+            self.0.borrow_mut().requested = Some(config);
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        let state = self.0.borrow();
+        state.recording || state.requested.is_some()
+    }
+
+    /// Saves the next frame as a PNG, redrawn at the configured size with
+    /// the same seed and simulation state. Motion trails are left out; they
+    /// only exist at screen resolution.
+    pub fn screenshot(&self, config: ScreenshotConfig) {
+        self.0.borrow_mut().screenshot = Some(config);
+    }
+
+    pub(crate) fn take_screenshot(&self) -> Option<ScreenshotConfig> {
+        self.0.borrow_mut().screenshot.take()
+    }
+
+    pub(crate) fn take_request(&self) -> Option<CaptureConfig> {
+        let mut state = self.0.borrow_mut();
+        let config = state.requested.take()?;
+        state.recording = true;
+        Some(config)
+    }
+}
+
+enum Output {
+    WebM {
+        recorder: MediaRecorder,
+        track: CanvasCaptureMediaStreamTrack,
+        // Wall clock time owed to the next frame, in 60 Hz frames
+        pending: f64,
+    },
+    Gif {
+        context: CanvasRenderingContext2d,
+        encoder: Encoder<Vec<u8>>,
+        width: u16,
+        height: u16,
+    },
+}
+
+// Clears the capture's recording flag however the recording ends, including
+// when the starfield is torn down halfway through
+struct RecordingFlag(Capture);
+
+impl Drop for RecordingFlag {
+    fn drop(&mut self) {
+        (self.0).0.borrow_mut().recording = false;
+    }
+}
+
+/// A recording in progress, fed by the `Starfield` render loop.
+pub(crate) struct Recording {
+    _flag: RecordingFlag,
+    config: CaptureConfig,
+    output: Output,
+    frames: u32,
+}
+
+impl Recording {
+    /// `width` and `height` are the canvas size in CSS pixels.
+    pub fn start(capture: Capture, config: CaptureConfig, canvas: &HtmlCanvasElement, width: f64, height: f64) -> Self {
+        let output = match config.format {
+            CaptureFormat::WebM => start_webm(canvas, &config.file_name),
+            CaptureFormat::Gif => {
+                let scale = config.gif_scale.clamp(0.05, 1.0);
+                let (width, height) = ((width * scale).round().max(1.0) as u16, (height * scale).round().max(1.0) as u16);
+                let context = context_2d(&create_canvas(width as u32, height as u32));
+                let mut encoder = Encoder::new(Vec::new(), width, height, &[]).unwrap();
+                encoder.set_repeat(Repeat::Infinite).unwrap();
+                Output::Gif {
+                    context,
+                    encoder,
+                    width,
+                    height,
+                }
+            }
+        };
+
+        Recording {
+            _flag: RecordingFlag(capture),
+            config,
+            output,
+            frames: 0,
+        }
+    }
+
+    fn frame_step(&self) -> f64 {
+        60.0 / self.config.fps.max(1) as f64
+    }
+
+    /// The fixed step for the next frame, given the real time since the last
+    /// one, or `None` if no frame is due yet. WebM frames are timed by the
+    /// wall clock, so they're only drawn as real time catches up; GIF frames
+    /// carry their own delays and are drawn every time.
+    pub fn step(&mut self, dt: f64) -> Option<f64> {
+        let step = self.frame_step();
+        if let Output::WebM { pending, .. } = &mut self.output {
+            *pending += dt;
+            if *pending < step {
+                return None;
+            }
+            *pending = (*pending - step).min(step);
+        }
+        Some(step)
+    }
+
+    /// Records the frame just drawn on `canvas`. Returns `false` once the
+    /// recording is complete and should be finished.
+    pub fn add_frame(&mut self, canvas: &HtmlCanvasElement) -> bool {
+        match &mut self.output {
+            Output::WebM { track, .. } => track.request_frame(),
+            Output::Gif {
+                context,
+                encoder,
+                width,
+                height,
+            } => {
+                let (w, h) = (*width as f64, *height as f64);
+                context.draw_image_with_html_canvas_element_and_dw_and_dh(canvas, 0.0, 0.0, w, h).unwrap();
+                let mut pixels = context.get_image_data(0.0, 0.0, w, h).unwrap().data().0;
+                let mut frame = Frame::from_rgba_speed(*width, *height, &mut pixels, 10);
+                // Delays are in hundredths of a second; spread the rounding
+                // so the total length stays right
+                let fps = self.config.fps.max(1) as f64;
+                let at = |frame: u32| (frame as f64 * 100.0 / fps).round() as u32;
+                frame.delay = (at(self.frames + 1) - at(self.frames)) as u16;
+                encoder.write_frame(&frame).unwrap();
+            }
+        }
+        self.frames += 1;
+        (self.frames as f64) < self.config.seconds * self.config.fps as f64
+    }
+
+    /// Ends the recording and downloads the file.
+    pub fn finish(self) {
+        match self.output {
+            // The download happens in the recorder's stop handler
+            Output::WebM { recorder, .. } => recorder.stop().unwrap(),
+            Output::Gif { encoder, .. } => {
+                let bytes = encoder.into_inner().unwrap();
+                let parts = Array::of1(&Uint8Array::from(bytes.as_slice()));
+                let options = BlobPropertyBag::new();
+                options.set_type("image/gif");
+                let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &options).unwrap();
+                download(&blob, &format!("{}.gif", self.config.file_name));
+            }
+        }
+    }
+}
+
+fn start_webm(canvas: &HtmlCanvasElement, file_name: &str) -> Output {
+    // A rate of 0 only takes frames when asked, so the video gets exactly the
+    // frames that were stepped for it
+    let stream = canvas.capture_stream_with_frame_request_rate(0.0).unwrap();
+    let track = stream.get_video_tracks().get(0).unchecked_into::<CanvasCaptureMediaStreamTrack>();
+
+    let mime = ["video/webm;codecs=vp9", "video/webm;codecs=vp8", "video/webm"]
+        .into_iter()
+        .find(|mime| MediaRecorder::is_type_supported(mime))
+        .unwrap_or("video/webm");
+    let options = MediaRecorderOptions::new();
+    options.set_mime_type(mime);
+    options.set_video_bits_per_second(8_000_000);
+    let recorder = MediaRecorder::new_with_media_stream_and_media_recorder_options(&stream, &options).unwrap();
+
+    let chunks = Array::new();
+    let on_data = {
+        let chunks = chunks.clone();
+        Closure::<dyn FnMut(BlobEvent)>::new(move |e: BlobEvent| {
+            if let Some(data) = e.data() {
+                chunks.push(&data);
+            }
+        })
+    };
+    recorder.set_ondataavailable(Some(on_data.as_ref().unchecked_ref()));
+    // The last data arrives just before the recorder stops, so the data
+    // handler lives until then; this one frees itself after running
+    let on_stop = {
+        let recorder = recorder.clone();
+        let file_name = format!("{}.webm", file_name);
+        Closure::once_into_js(move || {
+            recorder.set_ondataavailable(None);
+            drop(on_data);
+            let options = BlobPropertyBag::new();
+            options.set_type("video/webm");
+            let blob = Blob::new_with_blob_sequence_and_options(&chunks, &options).unwrap();
+            download(&blob, &file_name);
+        })
+    };
+    recorder.set_onstop(Some(on_stop.unchecked_ref()));
+    recorder.start().unwrap();
+
+    Output::WebM {
+        recorder,
+        track,
+        pending: 0.0,
+    }
+}
+
+/// A canvas of the screenshot size, transformed so that drawing a
+/// `view_width` x `view_height` frame covers it. Returns the scale too.
+pub(crate) fn still_canvas(config: &ScreenshotConfig, view_width: f64, view_height: f64) -> (HtmlCanvasElement, CanvasRenderingContext2d, f64) {
+    let (width, height) = (config.width.clamp(1, MAX_SIDE) as f64, config.height.clamp(1, MAX_SIDE) as f64);
+    let canvas = create_canvas(width as u32, height as u32);
+    let context = context_2d(&canvas);
+    let scale = (width / view_width).max(height / view_height);
+    context.translate((width - view_width * scale) / 2.0, (height - view_height * scale) / 2.0).unwrap();
+    context.scale(scale, scale).unwrap();
+    (canvas, context, scale)
+}
+
+/// Encodes `canvas` as PNG and downloads it.
+pub(crate) fn save_png(canvas: &HtmlCanvasElement, file_name: &str) {
+    let file_name = format!("{}.png", file_name);
+    let on_blob = Closure::once_into_js(move |blob: Option<Blob>| {
+        if let Some(blob) = blob {
+            download(&blob, &file_name);
+        }
+    });
+    canvas.to_blob(on_blob.unchecked_ref()).unwrap();
+}
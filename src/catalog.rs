@@ -0,0 +1,149 @@
+//! Bright-star catalog bundled with the binary.
+//!
+//! `assets/bright_stars.bin` holds the naked-eye stars that make up the
+//! familiar constellation figures (J2000 positions and Johnson V / B-V values
+//! from the Yale Bright Star Catalogue), little-endian:
+//!
+//! ```text
+//! "HSC1"
+//! u16 star count, then per star:
+//!     u16 right ascension (full circle = 65536)
+//!     i16 declination (±90° = ±32767)
+//!     i16 visual magnitude × 100
+//!     i16 B-V color index × 100
+//!     u8 name length, name bytes (UTF-8)
+//! u8 constellation count, then per constellation:
+//!     u8 name length, u8 segment count, name bytes
+//!     per segment: u16 star index, u16 star index
+//! ```
+
+const BUNDLED: &[u8] = include_bytes!("../assets/bright_stars.bin");
+
+pub struct CatalogStar {
+    pub name: String,
+    /// Right ascension in degrees.
+    pub ra: f64,
+    /// Declination in degrees.
+    pub dec: f64,
+    pub magnitude: f64,
+    pub color_index: f64,
+}
+
+pub struct Constellation {
+    pub name: String,
+    /// Pairs of indices into `Catalog::stars`.
+    pub segments: Vec<(usize, usize)>,
+}
+
+pub struct Catalog {
+    pub stars: Vec<CatalogStar>,
+    pub constellations: Vec<Constellation>,
+}
+
+impl Catalog {
+    pub fn bundled() -> Self {
+        Catalog::parse(BUNDLED).expect("bundled star catalog is malformed")
+    }
+
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader { bytes, pos: 0 };
+        if reader.take(4)? != b"HSC1" {
+            return None;
+        }
+
+        let star_count = reader.u16()? as usize;
+        let mut stars = Vec::with_capacity(star_count);
+        for _ in 0..star_count {
+            let ra = reader.u16()? as f64 / 65536.0 * 360.0;
+            let dec = reader.i16()? as f64 / 32767.0 * 90.0;
+            let magnitude = reader.i16()? as f64 / 100.0;
+            let color_index = reader.i16()? as f64 / 100.0;
+            let name = reader.string()?;
+            stars.push(CatalogStar {
+                name,
+                ra,
+                dec,
+                magnitude,
+                color_index,
+            });
+        }
+
+        let constellation_count = reader.u8()? as usize;
+        let mut constellations = Vec::with_capacity(constellation_count);
+        for _ in 0..constellation_count {
+            let name_len = reader.u8()? as usize;
+            let segment_count = reader.u8()? as usize;
+            let name = String::from_utf8(reader.take(name_len)?.to_vec()).ok()?;
+            let mut segments = Vec::with_capacity(segment_count);
+            for _ in 0..segment_count {
+                let a = reader.u16()? as usize;
+                let b = reader.u16()? as usize;
+                if a >= star_count || b >= star_count {
+                    return None;
+                }
+                segments.push((a, b));
+            }
+            constellations.push(Constellation { name, segments });
+        }
+
+        Some(Catalog {
+            stars,
+            constellations,
+        })
+    }
+}
+
+/// Approximate RGB of a star from its B-V color index, interpolated between
+/// blackbody colors from hot blue (-0.4) to cool red (2.0) stars.
+pub fn color_from_index(color_index: f64) -> (u8, u8, u8) {
+    const TABLE: [(f64, (f64, f64, f64)); 7] = [
+        (-0.4, (155.0, 176.0, 255.0)),
+        (0.0, (202.0, 215.0, 255.0)),
+        (0.4, (248.0, 247.0, 255.0)),
+        (0.6, (255.0, 244.0, 234.0)),
+        (1.0, (255.0, 210.0, 161.0)),
+        (1.5, (255.0, 180.0, 120.0)),
+        (2.0, (255.0, 150.0, 90.0)),
+    ];
+
+    let bv = color_index.clamp(TABLE[0].0, TABLE[TABLE.len() - 1].0);
+    let i = TABLE.iter().position(|&(edge, _)| edge >= bv).unwrap_or(TABLE.len() - 1).max(1);
+    let (lo, a) = TABLE[i - 1];
+    let (hi, b) = TABLE[i];
+    let t = (bv - lo) / (hi - lo);
+    (
+        (a.0 + (b.0 - a.0) * t) as u8,
+        (a.1 + (b.1 - a.1) * t) as u8,
+        (a.2 + (b.2 - a.2) * t) as u8,
+    )
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let slice = self.bytes.get(self.pos..self.pos + len)?;
+        self.pos += len;
+        Some(slice)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    fn i16(&mut self) -> Option<i16> {
+        Some(i16::from_le_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u8()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+}
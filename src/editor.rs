@@ -0,0 +1,557 @@
+//! Development control panel: sliders and pickers bound to a running
+//! `Starfield`, with an export of the settings as a JSON or TOML preset.
+//! The shareable settings are kept in the page URL as they change, and the
+//! animation can be recorded to WebM or GIF or saved as a 4K PNG, or set to
+//! a music file.
+
+use crate::bloom::BloomConfig;
+use crate::capture::{Capture, CaptureConfig, CaptureFormat, ScreenshotConfig};
+use crate::effects::{BlackHoleConfig, GalaxyConfig, GravityConfig, SkyConfig, TunnelConfig, WarpConfig};
+use crate::nebula::NebulaConfig;
+use crate::optics::{OpticsConfig, SpikePattern};
+use crate::preset::Preset;
+use crate::reactive::{AudioReactive, ReactiveConfig};
+use crate::starfield::{StarEffect, Starfield};
+use crate::tween::Transition;
+use crate::url::UrlConfig;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+// Milliseconds the settings must stay put before they're written to the URL
+const URL_DELAY: i32 = 300;
+
+const SPIKES: [(SpikePattern, &str); 5] = [
+    (SpikePattern::None, "none"),
+    (SpikePattern::Four, "four"),
+    (SpikePattern::Six, "six"),
+    (SpikePattern::Eight, "eight"),
+    (SpikePattern::Jwst, "jwst"),
+];
+
+#[derive(Clone, PartialEq)]
+struct EditorState {
+    effect: StarEffect,
+    seed: u64,
+    speed: f64,
+    density: f64,
+    warp: WarpConfig,
+    galaxy: Option<GalaxyConfig>,
+    black_hole: BlackHoleConfig,
+    tunnel: TunnelConfig,
+    gravity: GravityConfig,
+    sky: SkyConfig,
+    nebula: Option<NebulaConfig>,
+    /// Palette the nebula was picked from, until its colors are edited.
+    palette: Option<String>,
+    trails: Option<f64>,
+    bloom: Option<BloomConfig>,
+    optics: Option<OpticsConfig>,
+}
+
+impl EditorState {
+    fn new(effect: StarEffect) -> Self {
+        EditorState {
+            effect,
+            seed: 1,
+            speed: 1.0,
+            density: 1.0,
+            warp: WarpConfig::default(),
+            galaxy: None,
+            black_hole: BlackHoleConfig::default(),
+            tunnel: TunnelConfig::default(),
+            gravity: GravityConfig::default(),
+            sky: SkyConfig::default(),
+            nebula: None,
+            palette: None,
+            trails: None,
+            bloom: None,
+            optics: None,
+        }
+    }
+
+    /// Starts from the settings in the page URL, so a shared link opens in
+    /// the editor as it was left.
+    fn from_url(effect: StarEffect, url: &UrlConfig) -> Self {
+        let mut state = EditorState::new(url.effect.unwrap_or(effect));
+        state.seed = url.seed.unwrap_or(state.seed);
+        state.speed = url.speed.unwrap_or(state.speed);
+        state.density = url.density.unwrap_or(state.density);
+        state.nebula = url.nebula();
+        state.palette = url.palette.clone();
+        state
+    }
+
+    fn url(&self) -> UrlConfig {
+        UrlConfig {
+            effect: Some(self.effect),
+            seed: Some(self.seed),
+            speed: Some(self.speed),
+            palette: self.palette.clone(),
+            density: Some(self.density),
+            screensaver: false,
+        }
+    }
+
+    /// The settings that apply to the selected effect, as a preset.
+    fn preset(&self) -> Preset {
+        let mut preset = Preset {
+            effect: self.effect,
+            seed: Some(self.seed),
+            nebula: self.nebula.clone(),
+            trails: self.trails,
+            bloom: self.bloom.clone(),
+            optics: self.optics.clone(),
+            ..Preset::default()
+        };
+        match self.effect {
+            StarEffect::Warp => preset.warp = Some(self.warp.clone()),
+            StarEffect::Spiral => preset.galaxy = self.galaxy.clone(),
+            StarEffect::BlackHole => preset.black_hole = Some(self.black_hole.clone()),
+            StarEffect::Tunnel => preset.tunnel = Some(self.tunnel.clone()),
+            StarEffect::Gravity => preset.gravity = Some(self.gravity.clone()),
+            StarEffect::Sky => preset.sky = Some(self.sky.clone()),
+            StarEffect::Twinkle => {}
+        }
+        preset
+    }
+}
+
+/// Callback that applies an edit to a copy of the state and stores it.
+fn edit<T: 'static>(state: &UseStateHandle<EditorState>, apply: impl Fn(&mut EditorState, T) + 'static) -> Callback<T> {
+    let state = state.clone();
+    Callback::from(move |value: T| {
+        let mut next = (*state).clone();
+        apply(&mut next, value);
+        state.set(next);
+    })
+}
+
+fn slider(label: &str, value: f64, (min, max, step): (f64, f64, f64), on_change: Callback<f64>) -> Html {
+    let oninput = Callback::from(move |e: InputEvent| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        if let Ok(value) = input.value().parse() {
+            on_change.emit(value);
+        }
+    });
+    // Show as many decimals as the step has
+    let decimals = (-step.log10()).ceil().max(0.0) as usize;
+    html! {
+        <label class="editor-row">
+            <span>{label}</span>
+            <input type="range" min={min.to_string()} max={max.to_string()} step={step.to_string()} value={value.to_string()} {oninput} />
+            <output>{format!("{:.*}", decimals, value)}</output>
+        </label>
+    }
+}
+
+fn number(label: &str, value: u64, on_change: Callback<u64>) -> Html {
+    let oninput = Callback::from(move |e: InputEvent| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        if let Ok(value) = input.value().parse() {
+            on_change.emit(value);
+        }
+    });
+    html! {
+        <label class="editor-row">
+            <span>{label}</span>
+            <input type="number" min="0" step="1" value={value.to_string()} {oninput} />
+        </label>
+    }
+}
+
+fn toggle(label: &str, value: bool, on_change: Callback<bool>) -> Html {
+    let onchange = Callback::from(move |e: Event| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        on_change.emit(input.checked());
+    });
+    html! {
+        <label class="editor-row">
+            <span>{label}</span>
+            <input type="checkbox" checked={value} {onchange} />
+        </label>
+    }
+}
+
+fn color(label: &str, (r, g, b): (u8, u8, u8), on_change: Callback<(u8, u8, u8)>) -> Html {
+    let oninput = Callback::from(move |e: InputEvent| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        let hex = input.value();
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2).unwrap_or("00"), 16).unwrap_or(0);
+        on_change.emit((channel(1), channel(3), channel(5)));
+    });
+    html! {
+        <label class="editor-row">
+            <span>{label}</span>
+            <input type="color" value={format!("#{:02x}{:02x}{:02x}", r, g, b)} {oninput} />
+        </label>
+    }
+}
+
+fn select(label: &str, options: &[&str], selected: usize, on_change: Callback<usize>) -> Html {
+    let onchange = Callback::from(move |e: Event| {
+        let select: HtmlSelectElement = e.target_unchecked_into();
+        on_change.emit(select.selected_index().max(0) as usize);
+    });
+    html! {
+        <label class="editor-row">
+            <span>{label}</span>
+            <select {onchange}>
+                { for options.iter().enumerate().map(|(i, option)| html! {
+                    <option selected={i == selected}>{*option}</option>
+                }) }
+            </select>
+        </label>
+    }
+}
+
+fn effect_controls(state: &UseStateHandle<EditorState>) -> Html {
+    match state.effect {
+        StarEffect::Warp => html! {
+            <>
+                { slider("Stars", state.warp.stars as f64, (50.0, 2000.0, 10.0), edit(state, |s, v: f64| s.warp.stars = v as usize)) }
+                { slider("Speed", state.warp.speed, (0.0, 5.0, 0.05), edit(state, |s, v| s.warp.speed = v)) }
+                { slider("Streak", state.warp.streak, (0.0, 120.0, 1.0), edit(state, |s, v| s.warp.streak = v)) }
+            </>
+        },
+        StarEffect::Spiral => {
+            let galaxy = match &state.galaxy {
+                Some(galaxy) => html! {
+                    <>
+                        { slider("Arms", galaxy.arms as f64, (1.0, 6.0, 1.0), edit(state, |s, v: f64| s.galaxy.as_mut().unwrap().arms = v as u32)) }
+                        { slider("Pitch", galaxy.pitch, (5.0, 40.0, 0.5), edit(state, |s, v| s.galaxy.as_mut().unwrap().pitch = v)) }
+                        { slider("Eccentricity", galaxy.eccentricity, (0.0, 0.6, 0.01), edit(state, |s, v| s.galaxy.as_mut().unwrap().eccentricity = v)) }
+                        { slider("Bulge", galaxy.bulge, (0.0, 0.4, 0.01), edit(state, |s, v| s.galaxy.as_mut().unwrap().bulge = v)) }
+                        { slider("Bar", galaxy.bar, (0.0, 0.5, 0.01), edit(state, |s, v| s.galaxy.as_mut().unwrap().bar = v)) }
+                        { slider("Dust", galaxy.dust, (0.0, 1.0, 0.01), edit(state, |s, v| s.galaxy.as_mut().unwrap().dust = v)) }
+                        { slider("Clumpiness", galaxy.clumpiness, (0.0, 0.5, 0.01), edit(state, |s, v| s.galaxy.as_mut().unwrap().clumpiness = v)) }
+                        { slider("Rotation", galaxy.rotation, (0.0, 0.02, 0.0005), edit(state, |s, v| s.galaxy.as_mut().unwrap().rotation = v)) }
+                        { slider("Stars", galaxy.stars as f64, (200.0, 5000.0, 50.0), edit(state, |s, v: f64| s.galaxy.as_mut().unwrap().stars = v as usize)) }
+                    </>
+                },
+                None => html! {},
+            };
+            html! {
+                <>
+                    { toggle("Galaxy", state.galaxy.is_some(), edit(state, |s, on: bool| s.galaxy = on.then(GalaxyConfig::default))) }
+                    { galaxy }
+                </>
+            }
+        }
+        StarEffect::BlackHole => html! {
+            <>
+                { slider("Einstein radius", state.black_hole.einstein_radius, (0.02, 0.3, 0.005), edit(state, |s, v| s.black_hole.einstein_radius = v)) }
+                { toggle("Accretion disk", state.black_hole.accretion_disk, edit(state, |s, on| s.black_hole.accretion_disk = on)) }
+                { slider("Inclination", state.black_hole.inclination, (0.0, 90.0, 1.0), edit(state, |s, v| s.black_hole.inclination = v)) }
+                { slider("Infalling", state.black_hole.infalling as f64, (0.0, 60.0, 1.0), edit(state, |s, v: f64| s.black_hole.infalling = v as usize)) }
+                { slider("Drift", state.black_hole.drift, (0.0, 2.0, 0.01), edit(state, |s, v| s.black_hole.drift = v)) }
+            </>
+        },
+        StarEffect::Tunnel => html! {
+            <>
+                { slider("Radius", state.tunnel.radius, (0.1, 1.0, 0.01), edit(state, |s, v| s.tunnel.radius = v)) }
+                { slider("Twist", state.tunnel.twist, (0.0, 0.02, 0.0005), edit(state, |s, v| s.tunnel.twist = v)) }
+                { slider("Curvature", state.tunnel.curvature, (0.0, 3.0, 0.05), edit(state, |s, v| s.tunnel.curvature = v)) }
+                { slider("Spin", state.tunnel.spin, (-0.02, 0.02, 0.0005), edit(state, |s, v| s.tunnel.spin = v)) }
+                { slider("Speed", state.tunnel.speed, (0.0, 30.0, 0.5), edit(state, |s, v| s.tunnel.speed = v)) }
+                { slider("Rings", state.tunnel.rings as f64, (0.0, 40.0, 1.0), edit(state, |s, v: f64| s.tunnel.rings = v as usize)) }
+            </>
+        },
+        StarEffect::Gravity => html! {
+            <>
+                { slider("Bodies", state.gravity.bodies as f64, (50.0, 3000.0, 50.0), edit(state, |s, v: f64| s.gravity.bodies = v as usize)) }
+                { slider("Clusters", state.gravity.clusters as f64, (1.0, 10.0, 1.0), edit(state, |s, v: f64| s.gravity.clusters = v as usize)) }
+                { slider("Theta", state.gravity.theta, (0.0, 1.5, 0.05), edit(state, |s, v| s.gravity.theta = v)) }
+                { slider("Softening", state.gravity.softening, (0.5, 20.0, 0.5), edit(state, |s, v| s.gravity.softening = v)) }
+                { slider("Drop mass", state.gravity.drop_mass, (10.0, 2000.0, 10.0), edit(state, |s, v| s.gravity.drop_mass = v)) }
+            </>
+        },
+        StarEffect::Sky => html! {
+            <>
+                { slider("Latitude", state.sky.latitude, (-90.0, 90.0, 0.5), edit(state, |s, v| s.sky.latitude = v)) }
+                { slider("Longitude", state.sky.longitude, (-180.0, 180.0, 0.5), edit(state, |s, v| s.sky.longitude = v)) }
+                { slider("Time scale", state.sky.time_scale, (0.0, 3600.0, 10.0), edit(state, |s, v| s.sky.time_scale = v)) }
+                { toggle("Constellations", state.sky.constellation_lines, edit(state, |s, on| s.sky.constellation_lines = on)) }
+                { toggle("Labels", state.sky.labels, edit(state, |s, on| s.sky.labels = on)) }
+            </>
+        },
+        StarEffect::Twinkle => html! {},
+    }
+}
+
+fn layer_controls(state: &UseStateHandle<EditorState>) -> Html {
+    let nebula = match &state.nebula {
+        Some(nebula) => html! {
+            <>
+                { palette_control(state) }
+                { for nebula.layers.iter().enumerate().map(|(i, layer)| html! {
+                    <>
+                        { color(&format!("Layer {}", i + 1), layer.color, edit(state, move |s, c| {
+                            s.nebula.as_mut().unwrap().layers[i].color = c;
+                            s.palette = None;
+                        })) }
+                        { slider("Opacity", layer.opacity, (0.0, 1.0, 0.01), edit(state, move |s, v| s.nebula.as_mut().unwrap().layers[i].opacity = v)) }
+                        { slider("Scale", layer.scale, (0.5, 8.0, 0.1), edit(state, move |s, v| s.nebula.as_mut().unwrap().layers[i].scale = v)) }
+                        { slider("Threshold", layer.threshold, (-1.0, 1.0, 0.01), edit(state, move |s, v| s.nebula.as_mut().unwrap().layers[i].threshold = v)) }
+                        { slider("Warp", layer.warp, (0.0, 8.0, 0.1), edit(state, move |s, v| s.nebula.as_mut().unwrap().layers[i].warp = v)) }
+                    </>
+                }) }
+                { slider("Drift", nebula.drift, (0.0, 0.003, 0.0001), edit(state, |s, v| s.nebula.as_mut().unwrap().drift = v)) }
+            </>
+        },
+        None => html! {},
+    };
+
+    let trails = match state.trails {
+        Some(decay) => slider("Decay", decay, (0.5, 0.98, 0.01), edit(state, |s, v| s.trails = Some(v))),
+        None => html! {},
+    };
+
+    let bloom = match &state.bloom {
+        Some(bloom) => html! {
+            <>
+                { slider("Threshold", bloom.threshold, (0.05, 0.95, 0.01), edit(state, |s, v| s.bloom.as_mut().unwrap().threshold = v)) }
+                { slider("Intensity", bloom.intensity, (0.0, 1.0, 0.01), edit(state, |s, v| s.bloom.as_mut().unwrap().intensity = v)) }
+                { slider("Levels", bloom.levels as f64, (1.0, 6.0, 1.0), edit(state, |s, v: f64| s.bloom.as_mut().unwrap().levels = v as u32)) }
+                { slider("Radius", bloom.radius, (0.0, 12.0, 0.5), edit(state, |s, v| s.bloom.as_mut().unwrap().radius = v)) }
+            </>
+        },
+        None => html! {},
+    };
+
+    let optics = match &state.optics {
+        Some(optics) => {
+            let selected = SPIKES.iter().position(|(spikes, _)| *spikes == optics.spikes).unwrap_or(0);
+            let names: Vec<&str> = SPIKES.iter().map(|(_, name)| *name).collect();
+            html! {
+                <>
+                    { select("Spikes", &names, selected, edit(state, |s, i: usize| s.optics.as_mut().unwrap().spikes = SPIKES[i].0)) }
+                    { slider("Spike intensity", optics.spike_intensity, (0.0, 2.0, 0.05), edit(state, |s, v| s.optics.as_mut().unwrap().spike_intensity = v)) }
+                    { slider("Spike length", optics.spike_length, (0.2, 3.0, 0.05), edit(state, |s, v| s.optics.as_mut().unwrap().spike_length = v)) }
+                    { slider("Rotation", optics.rotation, (0.0, std::f64::consts::PI, 0.01), edit(state, |s, v| s.optics.as_mut().unwrap().rotation = v)) }
+                    { slider("Halo", optics.halo_intensity, (0.0, 1.0, 0.01), edit(state, |s, v| s.optics.as_mut().unwrap().halo_intensity = v)) }
+                    { slider("Flare", optics.flare_intensity, (0.0, 1.0, 0.01), edit(state, |s, v| s.optics.as_mut().unwrap().flare_intensity = v)) }
+                </>
+            }
+        }
+        None => html! {},
+    };
+
+    html! {
+        <>
+            <h3>{"Nebula"}</h3>
+            { toggle("Enabled", state.nebula.is_some(), edit(state, |s, on: bool| {
+                s.nebula = on.then(NebulaConfig::default);
+                s.palette = on.then(|| "teal".to_string());
+            })) }
+            { nebula }
+            <h3>{"Trails"}</h3>
+            { toggle("Enabled", state.trails.is_some(), edit(state, |s, on: bool| s.trails = on.then_some(0.85))) }
+            { trails }
+            <h3>{"Bloom"}</h3>
+            { toggle("Enabled", state.bloom.is_some(), edit(state, |s, on: bool| s.bloom = on.then(BloomConfig::default))) }
+            { bloom }
+            <h3>{"Optics"}</h3>
+            { toggle("Enabled", state.optics.is_some(), edit(state, |s, on: bool| s.optics = on.then(OpticsConfig::default))) }
+            { optics }
+        </>
+    }
+}
+
+fn palette_control(state: &UseStateHandle<EditorState>) -> Html {
+    let mut names: Vec<&str> = NebulaConfig::palette_names().collect();
+    // Colors edited by hand no longer match a palette
+    if state.palette.is_none() {
+        names.push("custom");
+    }
+    let selected = names.iter().position(|name| Some(*name) == state.palette.as_deref()).unwrap_or(names.len() - 1);
+    let options = names.clone();
+    select("Palette", &names, selected, edit(state, move |s, i: usize| {
+        if let Some(nebula) = NebulaConfig::palette(options[i]) {
+            s.nebula = Some(nebula);
+            s.palette = Some(options[i].to_string());
+        }
+    }))
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct EffectEditorProps {
+    /// Effect selected when the editor opens, unless the URL names one.
+    #[prop_or(StarEffect::Warp)]
+    pub effect: StarEffect,
+}
+
+#[function_component(EffectEditor)]
+pub fn effect_editor(props: &EffectEditorProps) -> Html {
+    let state = use_state(|| EditorState::from_url(props.effect, &UrlConfig::from_location()));
+    let exported = use_state(|| None::<String>);
+    let capture = use_memo((), |_| Capture::new());
+    let reactive = use_state(|| None::<AudioReactive>);
+
+    // Rewriting the URL on every slider tick floods the history API, so it
+    // waits until the settings settle
+    use_effect_with(state.url(), |url| {
+        let url = url.clone();
+        let write = Closure::<dyn FnMut()>::new(move || url.replace_location());
+        let window = web_sys::window().unwrap();
+        let timeout = window
+            .set_timeout_with_callback_and_timeout_and_arguments_0(write.as_ref().unchecked_ref(), URL_DELAY)
+            .unwrap();
+        move || {
+            window.clear_timeout_with_handle(timeout);
+            drop(write);
+        }
+    });
+
+    let names: Vec<&str> = StarEffect::ALL.iter().map(|effect| effect.name()).collect();
+    let selected = StarEffect::ALL.iter().position(|effect| *effect == state.effect).unwrap_or(0);
+
+    let on_export_json = {
+        let (state, exported) = (state.clone(), exported.clone());
+        Callback::from(move |_: MouseEvent| exported.set(Some(state.preset().to_json())))
+    };
+    let on_export_toml = {
+        let (state, exported) = (state.clone(), exported.clone());
+        Callback::from(move |_: MouseEvent| exported.set(Some(state.preset().to_toml())))
+    };
+
+    let record = |format: CaptureFormat| {
+        let capture = capture.clone();
+        Callback::from(move |_: MouseEvent| {
+            capture.start(CaptureConfig {
+                format,
+                ..CaptureConfig::default()
+            })
+        })
+    };
+
+    let on_screenshot = {
+        let capture = capture.clone();
+        Callback::from(move |_: MouseEvent| capture.screenshot(ScreenshotConfig::default()))
+    };
+
+    let on_audio = {
+        let reactive = reactive.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Some(file) = input.files().and_then(|files| files.get(0)) {
+                if let Some(previous) = reactive.as_ref() {
+                    previous.stop();
+                }
+                reactive.set(AudioReactive::from_file(&file, ReactiveConfig::default()));
+            }
+        })
+    };
+    let on_stop_audio = {
+        let reactive = reactive.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(previous) = reactive.as_ref() {
+                previous.stop();
+            }
+            reactive.set(None);
+        })
+    };
+
+    html! {
+        <>
+            <style>
+                {r#"
+                    .editor {
+                        position: absolute;
+                        top: 1rem;
+                        right: 1rem;
+                        bottom: 1rem;
+                        width: 300px;
+                        overflow-y: auto;
+                        z-index: 2;
+                        padding: 1rem;
+                        background: rgba(10, 20, 20, 0.85);
+                        border: 1px solid rgba(0, 255, 200, 0.3);
+                        border-radius: 8px;
+                        color: white;
+                        font-size: 0.8rem;
+                    }
+                    .editor h3 {
+                        margin: 0.8rem 0 0.4rem;
+                        color: #00ffc8;
+                        font-size: 0.85rem;
+                    }
+                    .editor-row {
+                        display: grid;
+                        grid-template-columns: 6.5rem 1fr 3.5rem;
+                        align-items: center;
+                        gap: 0.4rem;
+                        margin: 0.2rem 0;
+                    }
+                    .editor-row output {
+                        text-align: right;
+                        opacity: 0.7;
+                    }
+                    .editor button {
+                        margin-top: 1rem;
+                        width: 100%;
+                        padding: 0.5rem;
+                        border: none;
+                        border-radius: 6px;
+                        background: #00ffc8;
+                        color: #021a1a;
+                        font-weight: 600;
+                        cursor: pointer;
+                    }
+                    .editor textarea {
+                        margin-top: 0.5rem;
+                        width: 100%;
+                        height: 8rem;
+                        font-family: monospace;
+                        font-size: 0.7rem;
+                    }
+                "#}
+            </style>
+            <Starfield
+                effect={state.effect}
+                seed={Some(state.seed)}
+                speed={state.speed}
+                density={state.density}
+                transition={Transition::default()}
+                capture={(*capture).clone()}
+                reactive={(*reactive).clone()}
+                warp={Some(state.warp.clone())}
+                galaxy={state.galaxy.clone()}
+                black_hole={Some(state.black_hole.clone())}
+                tunnel={Some(state.tunnel.clone())}
+                gravity={Some(state.gravity.clone())}
+                sky={Some(state.sky.clone())}
+                nebula={state.nebula.clone()}
+                trails={state.trails}
+                bloom={state.bloom.clone()}
+                optics={state.optics.clone()}
+            />
+            <div class="editor">
+                <h3>{"Effect"}</h3>
+                { select("Effect", &names, selected, edit(&state, |s, i: usize| s.effect = StarEffect::ALL[i])) }
+                { number("Seed", state.seed, edit(&state, |s, v| s.seed = v)) }
+                { slider("Speed", state.speed, (0.1, 5.0, 0.05), edit(&state, |s, v| s.speed = v)) }
+                { slider("Density", state.density, (0.05, 1.0, 0.05), edit(&state, |s, v| s.density = v)) }
+                { effect_controls(&state) }
+                { layer_controls(&state) }
+                <h3>{"Audio"}</h3>
+                <label class="editor-row">
+                    <span>{"Music"}</span>
+                    <input type="file" accept="audio/*" onchange={on_audio} />
+                </label>
+                if reactive.is_some() {
+                    <button onclick={on_stop_audio}>{"Stop music"}</button>
+                }
+                <button onclick={on_export_json}>{"Export JSON"}</button>
+                <button onclick={on_export_toml}>{"Export TOML"}</button>
+                <button onclick={record(CaptureFormat::WebM)}>{"Record WebM (5 s)"}</button>
+                <button onclick={record(CaptureFormat::Gif)}>{"Record GIF (5 s)"}</button>
+                <button onclick={on_screenshot}>{"Save PNG (3840×2160)"}</button>
+                if let Some(preset) = &*exported {
+                    <textarea readonly=true value={preset.clone()} />
+                }
+            </div>
+        </>
+    }
+}
@@ -0,0 +1,305 @@
+use super::{draw_core_glow, Effect};
+use crate::quality::Detail;
+use crate::random;
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use web_sys::CanvasRenderingContext2d;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlackHoleConfig {
+    /// Einstein radius as a fraction of the smaller canvas dimension.
+    pub einstein_radius: f64,
+    pub accretion_disk: bool,
+    /// Viewing angle of the disk in degrees; 90.0 is edge-on.
+    pub inclination: f64,
+    /// Number of stars spiralling into the hole.
+    pub infalling: usize,
+    /// Horizontal drift of the background field in pixels per frame, which
+    /// carries stars behind the lens.
+    pub drift: f64,
+}
+
+impl Default for BlackHoleConfig {
+    fn default() -> Self {
+        BlackHoleConfig {
+            einstein_radius: 0.12,
+            accretion_disk: true,
+            inclination: 78.0,
+            infalling: 12,
+            drift: 0.15,
+        }
+    }
+}
+
+pub struct BlackHole {
+    config: BlackHoleConfig,
+    rng: StdRng,
+    width: f64,
+    height: f64,
+    center_x: f64,
+    center_y: f64,
+    einstein_radius: f64,
+    shadow_radius: f64,
+    time: f64,
+    // x, y (unlensed, relative to the center), size, gray
+    stars: Vec<(f64, f64, f64, f64)>,
+    // radius, angle, size
+    disk: Vec<(f64, f64, f64)>,
+    // radius, angle, size
+    infalling: Vec<(f64, f64, f64)>,
+    detail: Detail,
+}
+
+impl BlackHole {
+    pub fn new(config: BlackHoleConfig, width: f64, height: f64) -> Self {
+        // Presets and URLs can carry anything; an empty lens would leave the
+        // disk and infalling stars with no range to be placed in
+        let einstein_radius = match config.einstein_radius {
+            radius if radius.is_nan() => BlackHoleConfig::default().einstein_radius,
+            radius => radius.clamp(0.01, 0.5),
+        };
+        let config = BlackHoleConfig { einstein_radius, ..config };
+        let mut rng = random::rng();
+        let einstein_radius = width.min(height) * config.einstein_radius;
+        let shadow_radius = einstein_radius * 0.35;
+
+        let stars = (0..400)
+            .map(|_| {
+                (
+                    rng.gen_range(-width / 2.0..width / 2.0),
+                    rng.gen_range(-height / 2.0..height / 2.0),
+                    rng.gen_range(0.5..1.8),
+                    rng.gen_range(120.0..220.0),
+                )
+            })
+            .collect();
+
+        let disk = if config.accretion_disk {
+            (0..600)
+                .map(|_| {
+                    (
+                        rng.gen_range(shadow_radius * 1.4..shadow_radius * 4.0),
+                        rng.gen_range(0.0..std::f64::consts::PI * 2.0),
+                        rng.gen_range(0.6..1.6),
+                    )
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let infalling = (0..config.infalling)
+            .map(|_| {
+                (
+                    rng.gen_range(shadow_radius * 3.0..einstein_radius * 3.0),
+                    rng.gen_range(0.0..std::f64::consts::PI * 2.0),
+                    rng.gen_range(1.0..2.0),
+                )
+            })
+            .collect();
+
+        BlackHole {
+            config,
+            rng,
+            width,
+            height,
+            center_x: width / 2.0,
+            center_y: height / 2.0,
+            einstein_radius,
+            shadow_radius,
+            time: 0.0,
+            stars,
+            disk,
+            infalling,
+            detail: Detail::default(),
+        }
+    }
+
+    /// Point-mass lens: a source at offset `(x, y)` from the lens appears as two
+    /// images on the same line, at `theta = (beta ± sqrt(beta² + 4 theta_e²)) / 2`.
+    /// Returns both images as `(x, y, magnification, tangential stretch)`.
+    fn lens(&self, x: f64, y: f64) -> [(f64, f64, f64, f64); 2] {
+        let re = self.einstein_radius;
+        let beta = (x * x + y * y).sqrt().max(0.01);
+        let u = beta / re;
+        let root = (beta * beta + 4.0 * re * re).sqrt();
+        let total = (u * u + 2.0) / (u * (u * u + 4.0).sqrt());
+
+        let image = |theta: f64, magnification: f64| {
+            let stretch = (theta / beta).abs().min(12.0);
+            (x / beta * theta, y / beta * theta, magnification, stretch)
+        };
+        [
+            image((beta + root) / 2.0, (total + 1.0) / 2.0),
+            image((beta - root) / 2.0, (total - 1.0) / 2.0),
+        ]
+    }
+
+    fn disk_position(&self, radius: f64, angle: f64) -> (f64, f64) {
+        let squash = self.config.inclination.to_radians().cos();
+        (angle.cos() * radius, angle.sin() * radius * squash)
+    }
+
+    fn draw_disk_particle(&self, context: &CanvasRenderingContext2d, x: f64, y: f64, radius: f64, angle: f64, size: f64) {
+        // Relativistic beaming: the side moving towards us is brighter
+        let doppler = 1.0 + angle.cos() * 0.6;
+        let heat = 1.0 - (radius / (self.shadow_radius * 4.0)).min(1.0);
+        let alpha = (0.15 + heat * 0.5) * doppler * 0.6;
+        let color = format!(
+            "rgba(255, {}, {}, {})",
+            (170.0 + heat * 70.0) as u32,
+            (110.0 + heat * 110.0) as u32,
+            alpha.min(1.0)
+        );
+        context.set_fill_style_str(&color);
+        context.begin_path();
+        context.arc(self.center_x + x, self.center_y + y, size, 0.0, std::f64::consts::PI * 2.0).unwrap();
+        context.fill();
+    }
+}
+
+impl Effect for BlackHole {
+    fn background(&self) -> &'static str {
+        "#040404"
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.time += dt;
+        let (width, height) = (self.width, self.height);
+
+        let drift = self.config.drift * dt;
+        self.stars.iter_mut().for_each(|star| {
+            star.0 += drift;
+            if star.0 > width / 2.0 {
+                star.0 -= width;
+            }
+        });
+
+        // Keplerian orbits: angular speed falls off as r^-1.5
+        let k = 0.6 * self.shadow_radius.sqrt();
+        self.disk.iter_mut().for_each(|particle| {
+            particle.1 += k / particle.0.powf(1.5) * dt;
+        });
+
+        let rng = &mut self.rng;
+        let shadow_radius = self.shadow_radius;
+        let einstein_radius = self.einstein_radius;
+        self.infalling.iter_mut().for_each(|star| {
+            star.1 += k / star.0.powf(1.5) * dt;
+            // Orbital decay speeds up close to the hole
+            star.0 -= 0.05 * dt * (einstein_radius / star.0);
+            if star.0 < shadow_radius {
+                star.0 = rng.gen_range(einstein_radius * 2.0..einstein_radius * 3.0).min(width.max(height) / 2.0);
+                star.1 = rng.gen_range(0.0..std::f64::consts::PI * 2.0);
+            }
+        });
+    }
+
+    fn draw(&self, context: &CanvasRenderingContext2d) {
+        let (center_x, center_y) = (self.center_x, self.center_y);
+
+        // Lensed background field: every star shows up twice, and stars close
+        // to the line of sight smear out along the Einstein ring
+        self.stars.iter().take(self.detail.count(self.stars.len())).for_each(|&(x, y, size, gray)| {
+            for (ix, iy, magnification, stretch) in self.lens(x, y) {
+                let brightness = magnification.min(4.0);
+                let alpha = (0.15 + 0.5 * brightness / 4.0) * (gray / 220.0);
+                if alpha < 0.02 {
+                    continue;
+                }
+                let color = format!("rgba({}, {}, {}, {})", gray as u32, gray as u32, gray as u32, alpha);
+                context.set_fill_style_str(&color);
+                context.begin_path();
+                let tangent = iy.atan2(ix) + std::f64::consts::PI / 2.0;
+                context
+                    .ellipse(
+                        center_x + ix,
+                        center_y + iy,
+                        size * stretch.max(1.0),
+                        size,
+                        tangent,
+                        0.0,
+                        std::f64::consts::PI * 2.0,
+                    )
+                    .unwrap();
+                context.fill();
+            }
+        });
+
+        let pulse = (self.time * 0.01).sin() * 0.5 + 0.5;
+        draw_core_glow(context, center_x, center_y, self.einstein_radius / 60.0, pulse);
+
+        // Far side of the disk, bent up over the shadow by the lens
+        self.disk.iter().filter(|particle| particle.1.sin() < 0.0).for_each(|&(radius, angle, size)| {
+            let (x, y) = self.disk_position(radius, angle);
+            let [(ix, iy, _, _), _] = self.lens(x, y);
+            self.draw_disk_particle(context, ix, iy, radius, angle, size);
+        });
+
+        // Event horizon shadow
+        context.set_fill_style_str("#000000");
+        context.begin_path();
+        context.arc(center_x, center_y, self.shadow_radius, 0.0, std::f64::consts::PI * 2.0).unwrap();
+        context.fill();
+
+        // Photon ring
+        let ring_color = format!("rgba(255, 220, 180, {})", 0.25 + pulse * 0.1);
+        context.set_stroke_style_str(&ring_color);
+        context.set_line_width(1.5);
+        context.begin_path();
+        context.arc(center_x, center_y, self.shadow_radius * 1.05, 0.0, std::f64::consts::PI * 2.0).unwrap();
+        context.stroke();
+
+        // Near side of the disk passes in front of the shadow
+        self.disk.iter().filter(|particle| particle.1.sin() >= 0.0).for_each(|&(radius, angle, size)| {
+            let (x, y) = self.disk_position(radius, angle);
+            self.draw_disk_particle(context, x, y, radius, angle, size);
+        });
+
+        self.infalling.iter().for_each(|&(radius, angle, size)| {
+            let (x, y) = self.disk_position(radius, angle);
+            let heat = (self.einstein_radius / radius).min(3.0) / 3.0;
+            let alpha = 0.4 + heat * 0.5;
+            let color = format!("rgba(230, 230, 255, {})", alpha);
+            context.set_fill_style_str(&color);
+            context.begin_path();
+            context.arc(center_x + x, center_y + y, size, 0.0, std::f64::consts::PI * 2.0).unwrap();
+            context.fill();
+
+            // Tidal glow grows as the star nears the horizon
+            let glow_color = format!("rgba(230, 230, 255, {})", alpha * 0.2);
+            context.set_fill_style_str(&glow_color);
+            context.begin_path();
+            context.arc(center_x + x, center_y + y, size * (2.0 + heat * 3.0), 0.0, std::f64::consts::PI * 2.0).unwrap();
+            context.fill();
+        });
+    }
+
+    fn star_count(&self) -> usize {
+        self.detail.count(self.stars.len()) + self.infalling.len()
+    }
+
+    fn set_detail(&mut self, detail: Detail) {
+        self.detail = detail;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degenerate_einstein_radius_is_clamped() {
+        for einstein_radius in [0.0, -0.3, f64::NAN] {
+            let config = BlackHoleConfig {
+                einstein_radius,
+                ..BlackHoleConfig::default()
+            };
+            let black_hole = BlackHole::new(config, 800.0, 600.0);
+            assert!(black_hole.einstein_radius > 0.0);
+            assert_eq!(black_hole.disk.len(), 600);
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use super::Effect;
+use crate::quality::Detail;
+use crate::random;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use web_sys::CanvasRenderingContext2d;
+
+/// Shape parameters for the density-wave galaxy.
+///
+/// Every star moves on a closed orbit `r = a * (1 - e * cos(arms * (phi - tilt(a))))`.
+/// The orbit tilt grows logarithmically with `a`, so neighbouring orbits crowd
+/// together along logarithmic spirals with the configured pitch angle. Those
+/// crowded regions are the arms; the stars themselves keep circulating through
+/// them at the speed given by the rotation curve.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GalaxyConfig {
+    pub arms: u32,
+    /// Pitch angle of the arms in degrees; small values wind tightly.
+    pub pitch: f64,
+    /// Orbit eccentricity in the disk, i.e. how pronounced the arms are.
+    pub eccentricity: f64,
+    /// Bulge radius as a fraction of the galaxy radius.
+    pub bulge: f64,
+    /// Bar half-length as a fraction of the galaxy radius; 0.0 for no bar.
+    pub bar: f64,
+    /// Opacity of the dust lanes along the inner edge of the arms.
+    pub dust: f64,
+    /// Random per-star orbit tilt in radians, breaking the arms into patches.
+    pub clumpiness: f64,
+    /// Angular speed on the flat part of the rotation curve, radians per frame.
+    pub rotation: f64,
+    pub stars: usize,
+}
+
+impl GalaxyConfig {
+    /// Two long, well-defined arms with strong dust lanes, like M51.
+    pub fn grand_design() -> Self {
+        GalaxyConfig {
+            arms: 2,
+            pitch: 14.0,
+            eccentricity: 0.35,
+            bulge: 0.12,
+            bar: 0.0,
+            dust: 0.6,
+            clumpiness: 0.05,
+            rotation: 0.004,
+            stars: 1500,
+        }
+    }
+
+    /// A straight bar through the bulge with arms starting at its ends.
+    pub fn barred() -> Self {
+        GalaxyConfig {
+            arms: 2,
+            pitch: 18.0,
+            eccentricity: 0.3,
+            bulge: 0.15,
+            bar: 0.3,
+            dust: 0.4,
+            clumpiness: 0.08,
+            rotation: 0.004,
+            stars: 1500,
+        }
+    }
+
+    /// Short, patchy arm fragments without a coherent pattern.
+    pub fn flocculent() -> Self {
+        GalaxyConfig {
+            arms: 4,
+            pitch: 28.0,
+            eccentricity: 0.12,
+            bulge: 0.1,
+            bar: 0.0,
+            dust: 0.2,
+            clumpiness: 0.6,
+            rotation: 0.004,
+            stars: 1500,
+        }
+    }
+}
+
+impl Default for GalaxyConfig {
+    fn default() -> Self {
+        GalaxyConfig::grand_design()
+    }
+}
+
+// Stars and dust share the same orbit description
+struct Orbit {
+    a: f64,
+    eccentricity: f64,
+    tilt: f64,
+    phi: f64,
+    omega: f64,
+}
+
+pub struct Galaxy {
+    config: GalaxyConfig,
+    center_x: f64,
+    center_y: f64,
+    radius: f64,
+    time: f64,
+    // orbit, size, gray, tint (0 = disk, 1 = bulge)
+    stars: Vec<(Orbit, f64, f64, u8)>,
+    // orbit, size
+    dust: Vec<(Orbit, f64)>,
+    detail: Detail,
+}
+
+impl Galaxy {
+    pub fn new(config: GalaxyConfig, width: f64, height: f64) -> Self {
+        // Presets and URLs can carry anything; keep the geometry sound
+        let config = GalaxyConfig {
+            pitch: config.pitch.clamp(1.0, 89.0),
+            bulge: config.bulge.clamp(0.0, 0.7),
+            bar: config.bar.clamp(0.0, 0.7),
+            ..config
+        };
+        let mut rng = random::rng();
+        let radius = width.min(height) / 2.0 * 0.9;
+        let bulge_radius = config.bulge * radius;
+        let bulge_fraction = (config.bulge * 2.0).min(0.5);
+
+        let stars = (0..config.stars)
+            .map(|_| {
+                if rng.gen_bool(bulge_fraction) {
+                    // Roughly Gaussian bulge, nearly circular orbits
+                    let a = (rng.gen_range(0.0..1.0) + rng.gen_range(0.0..1.0) + rng.gen_range(0.0..1.0) - 1.5f64).abs()
+                        * bulge_radius
+                        + 2.0;
+                    let orbit = Orbit::new(&config, a, radius, 0.0, &mut rng);
+                    (orbit, rng.gen_range(0.6..1.8), rng.gen_range(170.0..230.0), 1)
+                } else {
+                    // Exponential disk
+                    let a = (-rng.gen_range(0.001f64..1.0).ln() * radius * 0.35).min(radius) + bulge_radius * 0.5;
+                    let orbit = Orbit::new(&config, a, radius, 0.0, &mut rng);
+                    (orbit, rng.gen_range(0.5..2.2), rng.gen_range(130.0..220.0), 0)
+                }
+            })
+            .collect();
+
+        let dust_count = (config.stars as f64 * config.dust * 0.3) as usize;
+        let dust = (0..dust_count)
+            .map(|_| {
+                let a = rng.gen_range(bulge_radius.max(config.bar * radius)..radius * 0.8);
+                // Dust sits slightly ahead of the stellar arm, on its inner edge
+                let orbit = Orbit::new(&config, a, radius, -0.3, &mut rng);
+                (orbit, rng.gen_range(3.0..8.0))
+            })
+            .collect();
+
+        Galaxy {
+            config,
+            center_x: width / 2.0,
+            center_y: height / 2.0,
+            radius,
+            time: 0.0,
+            stars,
+            dust,
+            detail: Detail::default(),
+        }
+    }
+
+    fn position(&self, orbit: &Orbit) -> (f64, f64) {
+        // The whole pattern turns slowly as a rigid body
+        let pattern = self.time * self.config.rotation * 0.15;
+        let m = self.config.arms as f64;
+        let r = orbit.a * (1.0 - orbit.eccentricity * (m * (orbit.phi - orbit.tilt)).cos());
+        let angle = orbit.phi + pattern;
+        (self.center_x + angle.cos() * r, self.center_y + angle.sin() * r)
+    }
+}
+
+impl Orbit {
+    fn new(config: &GalaxyConfig, a: f64, radius: f64, tilt_offset: f64, rng: &mut impl Rng) -> Self {
+        let bar_radius = config.bar * radius;
+        let inner = bar_radius.max(config.bulge * radius * 0.5).max(1.0);
+
+        // Logarithmic spiral: tilt = ln(a / a0) / tan(pitch). Inside the bar all
+        // orbits share the same orientation, which is what makes it a bar.
+        let (tilt, eccentricity) = if a < bar_radius {
+            (0.0, 0.5)
+        } else {
+            let tilt = (a / inner).max(1.0).ln() / config.pitch.to_radians().tan();
+            let ramp = (a / inner).min(1.0);
+            let taper = 1.0 - 0.5 * (a / radius).min(1.0);
+            (tilt, config.eccentricity * ramp * taper)
+        };
+        let jitter = if config.clumpiness > 0.0 {
+            rng.gen_range(-config.clumpiness..config.clumpiness)
+        } else {
+            0.0
+        };
+
+        // Rotation curve rising through the core and flattening outside it
+        let turnover = radius * 0.15;
+        let velocity = config.rotation * turnover * (1.0 - (-a / turnover).exp());
+
+        Orbit {
+            a,
+            eccentricity,
+            tilt: tilt + tilt_offset + jitter,
+            phi: rng.gen_range(0.0..std::f64::consts::PI * 2.0),
+            omega: velocity / a.max(1.0),
+        }
+    }
+}
+
+impl Effect for Galaxy {
+    fn background(&self) -> &'static str {
+        "#060606"
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.time += dt;
+        self.stars.iter_mut().for_each(|star| star.0.phi += star.0.omega * dt);
+        self.dust.iter_mut().for_each(|dust| dust.0.phi += dust.0.omega * dt);
+    }
+
+    fn draw(&self, context: &CanvasRenderingContext2d) {
+        // Bulge glow
+        let bulge_radius = (self.config.bulge * self.radius).max(10.0);
+        let bulge_glow = "rgba(220, 200, 170, 0.05)";
+        context.set_fill_style_str(bulge_glow);
+        context.begin_path();
+        context.arc(self.center_x, self.center_y, bulge_radius * 1.5, 0.0, std::f64::consts::PI * 2.0).unwrap();
+        context.fill();
+
+        self.stars.iter().take(self.detail.count(self.stars.len())).for_each(|(orbit, size, gray, tint)| {
+            let (x, y) = self.position(orbit);
+            let dist_ratio = (orbit.a / self.radius).min(1.0);
+            let alpha = 0.35 + (1.0 - dist_ratio) * 0.45;
+
+            // Old, yellowish stars in the bulge; young, bluish ones in the disk
+            let (r, g, b) = match tint {
+                1 => (*gray, gray * 0.92, gray * 0.8),
+                _ => (gray * 0.9, gray * 0.95, *gray),
+            };
+            let color = format!("rgba({}, {}, {}, {})", r as u32, g as u32, b as u32, alpha);
+            context.set_fill_style_str(&color);
+            context.begin_path();
+            context.arc(x, y, size * 0.8, 0.0, std::f64::consts::PI * 2.0).unwrap();
+            context.fill();
+
+            if self.detail.glow && *size > 1.8 {
+                let glow_color = format!("rgba({}, {}, {}, {})", r as u32, g as u32, b as u32, alpha * 0.15);
+                context.set_fill_style_str(&glow_color);
+                context.begin_path();
+                context.arc(x, y, size * 2.0, 0.0, std::f64::consts::PI * 2.0).unwrap();
+                context.fill();
+            }
+        });
+
+        if self.config.dust > 0.0 {
+            let dust_color = format!("rgba(4, 4, 4, {})", self.config.dust * 0.25);
+            context.set_fill_style_str(&dust_color);
+            self.dust.iter().for_each(|(orbit, size)| {
+                let (x, y) = self.position(orbit);
+                context.begin_path();
+                context.arc(x, y, *size, 0.0, std::f64::consts::PI * 2.0).unwrap();
+                context.fill();
+            });
+        }
+
+        let inner_core = "rgba(235, 220, 190, 0.12)";
+        context.set_fill_style_str(inner_core);
+        context.begin_path();
+        context.arc(self.center_x, self.center_y, bulge_radius * 0.5, 0.0, std::f64::consts::PI * 2.0).unwrap();
+        context.fill();
+    }
+
+    fn star_count(&self) -> usize {
+        self.detail.count(self.stars.len())
+    }
+
+    fn set_detail(&mut self, detail: Detail) {
+        self.detail = detail;
+    }
+}
@@ -0,0 +1,337 @@
+use super::{Effect, Pointer};
+use crate::quality::Detail;
+use crate::random;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use web_sys::CanvasRenderingContext2d;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GravityConfig {
+    pub bodies: usize,
+    pub clusters: usize,
+    /// Barnes–Hut opening angle: a tree node is treated as a single mass when
+    /// `size / distance < theta`. 0.0 degrades to the exact O(n²) sum.
+    pub theta: f64,
+    /// Plummer softening length in pixels, avoids infinite kicks on close passes.
+    pub softening: f64,
+    /// Mass of the body dropped on click, in units of a single star.
+    pub drop_mass: f64,
+}
+
+impl Default for GravityConfig {
+    fn default() -> Self {
+        GravityConfig {
+            bodies: 600,
+            clusters: 4,
+            theta: 0.6,
+            softening: 4.0,
+            drop_mass: 400.0,
+        }
+    }
+}
+
+struct Body {
+    x: f64,
+    y: f64,
+    vx: f64,
+    vy: f64,
+    ax: f64,
+    ay: f64,
+    mass: f64,
+}
+
+/// Quadtree node. Leaves hold at most one body; every node tracks the total
+/// mass and center of mass of everything below it.
+struct Node {
+    cx: f64,
+    cy: f64,
+    half: f64,
+    mass: f64,
+    mx: f64,
+    my: f64,
+    body: Option<usize>,
+    children: Option<[usize; 4]>,
+}
+
+/// Barnes–Hut tree stored as a flat arena, rebuilt every step.
+struct QuadTree {
+    nodes: Vec<Node>,
+}
+
+impl QuadTree {
+    fn build(bodies: &[Body]) -> Self {
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for body in bodies {
+            min_x = min_x.min(body.x);
+            min_y = min_y.min(body.y);
+            max_x = max_x.max(body.x);
+            max_y = max_y.max(body.y);
+        }
+        let half = ((max_x - min_x).max(max_y - min_y) / 2.0).max(1.0) + 1.0;
+
+        let mut tree = QuadTree {
+            nodes: vec![Node::new((min_x + max_x) / 2.0, (min_y + max_y) / 2.0, half)],
+        };
+        for i in 0..bodies.len() {
+            tree.insert(0, i, bodies, 0);
+        }
+        tree
+    }
+
+    fn insert(&mut self, node: usize, i: usize, bodies: &[Body], depth: u32) {
+        let body = &bodies[i];
+
+        // Accumulate the center of mass on the way down
+        let n = &mut self.nodes[node];
+        let total = n.mass + body.mass;
+        n.mx = (n.mx * n.mass + body.x * body.mass) / total;
+        n.my = (n.my * n.mass + body.y * body.mass) / total;
+        n.mass = total;
+
+        // Coincident bodies would split forever; past this depth they just
+        // share the leaf's aggregate mass
+        if depth > 32 {
+            return;
+        }
+
+        if let Some(children) = self.nodes[node].children {
+            let child = children[self.nodes[node].quadrant(body.x, body.y)];
+            self.insert(child, i, bodies, depth + 1);
+            return;
+        }
+
+        match self.nodes[node].body.take() {
+            None if self.nodes[node].mass == body.mass => self.nodes[node].body = Some(i),
+            existing => {
+                self.subdivide(node);
+                let children = self.nodes[node].children.unwrap();
+                if let Some(j) = existing {
+                    let other = &bodies[j];
+                    let child = children[self.nodes[node].quadrant(other.x, other.y)];
+                    self.insert(child, j, bodies, depth + 1);
+                }
+                let child = children[self.nodes[node].quadrant(body.x, body.y)];
+                self.insert(child, i, bodies, depth + 1);
+            }
+        }
+    }
+
+    fn subdivide(&mut self, node: usize) {
+        let (cx, cy, half) = (self.nodes[node].cx, self.nodes[node].cy, self.nodes[node].half / 2.0);
+        let first = self.nodes.len();
+        self.nodes.push(Node::new(cx - half, cy - half, half));
+        self.nodes.push(Node::new(cx + half, cy - half, half));
+        self.nodes.push(Node::new(cx - half, cy + half, half));
+        self.nodes.push(Node::new(cx + half, cy + half, half));
+        self.nodes[node].children = Some([first, first + 1, first + 2, first + 3]);
+    }
+
+    fn acceleration(&self, node: usize, x: f64, y: f64, theta: f64, softening: f64) -> (f64, f64) {
+        let n = &self.nodes[node];
+        if n.mass == 0.0 {
+            return (0.0, 0.0);
+        }
+
+        let dx = n.mx - x;
+        let dy = n.my - y;
+        let dist_sq = dx * dx + dy * dy;
+
+        match n.children {
+            Some(children) if (n.half * 2.0) * (n.half * 2.0) > theta * theta * dist_sq => {
+                children.iter().fold((0.0, 0.0), |(ax, ay), &child| {
+                    let (cx, cy) = self.acceleration(child, x, y, theta, softening);
+                    (ax + cx, ay + cy)
+                })
+            }
+            _ => {
+                // A leaf containing the body itself contributes nothing
+                if dist_sq < 1e-12 {
+                    return (0.0, 0.0);
+                }
+                let r2 = dist_sq + softening * softening;
+                let inv = n.mass / (r2 * r2.sqrt());
+                (dx * inv, dy * inv)
+            }
+        }
+    }
+}
+
+impl Node {
+    fn new(cx: f64, cy: f64, half: f64) -> Self {
+        Node {
+            cx,
+            cy,
+            half,
+            mass: 0.0,
+            mx: 0.0,
+            my: 0.0,
+            body: None,
+            children: None,
+        }
+    }
+
+    fn quadrant(&self, x: f64, y: f64) -> usize {
+        (if x >= self.cx { 1 } else { 0 }) + (if y >= self.cy { 2 } else { 0 })
+    }
+}
+
+pub struct Gravity {
+    config: GravityConfig,
+    width: f64,
+    height: f64,
+    bodies: Vec<Body>,
+    detail: Detail,
+}
+
+impl Gravity {
+    pub fn new(config: GravityConfig, width: f64, height: f64) -> Self {
+        let mut rng = random::rng();
+        let per_cluster = config.bodies / config.clusters.max(1);
+        let mut bodies = Vec::with_capacity(config.bodies);
+
+        for _ in 0..config.clusters.max(1) {
+            let cx = rng.gen_range(width * 0.2..width * 0.8);
+            let cy = rng.gen_range(height * 0.2..height * 0.8);
+            let radius = width.min(height) * rng.gen_range(0.06..0.12);
+            // Clusters drift slowly so they meet rather than collapse in place
+            let (drift_x, drift_y) = (rng.gen_range(-0.15..0.15), rng.gen_range(-0.15..0.15));
+            let spin = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+
+            for _ in 0..per_cluster {
+                let angle = rng.gen_range(0.0..std::f64::consts::PI * 2.0);
+                let r = radius * rng.gen_range(0.0f64..1.0).sqrt();
+                // Roughly circular velocity for the enclosed mass
+                let enclosed = per_cluster as f64 * (r / radius).powi(2);
+                let v = (enclosed / (r + config.softening)).sqrt() * 0.7 * spin;
+                bodies.push(Body {
+                    x: cx + angle.cos() * r,
+                    y: cy + angle.sin() * r,
+                    vx: drift_x - angle.sin() * v,
+                    vy: drift_y + angle.cos() * v,
+                    ax: 0.0,
+                    ay: 0.0,
+                    mass: 1.0,
+                });
+            }
+        }
+
+        let mut gravity = Gravity {
+            config,
+            width,
+            height,
+            bodies,
+            detail: Detail::default(),
+        };
+        gravity.compute_accelerations();
+        gravity
+    }
+
+    fn compute_accelerations(&mut self) {
+        let tree = QuadTree::build(&self.bodies);
+        let (theta, softening) = (self.config.theta, self.config.softening);
+        self.bodies.iter_mut().for_each(|body| {
+            let (ax, ay) = tree.acceleration(0, body.x, body.y, theta, softening);
+            body.ax = ax;
+            body.ay = ay;
+        });
+    }
+
+    /// One kick-drift-kick leapfrog step. Being symplectic, it keeps bound
+    /// orbits bound instead of slowly spiralling them out like Euler would.
+    fn step(&mut self, dt: f64) {
+        self.bodies.iter_mut().for_each(|body| {
+            body.vx += body.ax * dt / 2.0;
+            body.vy += body.ay * dt / 2.0;
+            body.x += body.vx * dt;
+            body.y += body.vy * dt;
+        });
+
+        self.compute_accelerations();
+
+        self.bodies.iter_mut().for_each(|body| {
+            body.vx += body.ax * dt / 2.0;
+            body.vy += body.ay * dt / 2.0;
+        });
+    }
+}
+
+impl Effect for Gravity {
+    fn background(&self) -> &'static str {
+        "#050505"
+    }
+
+    fn update(&mut self, dt: f64) {
+        // Slingshots need small steps; split long frames up
+        let substeps = dt.ceil().max(1.0);
+        for _ in 0..substeps as u32 {
+            self.step(dt / substeps);
+        }
+
+        // Forget anything flung far off screen
+        let (width, height) = (self.width, self.height);
+        self.bodies.retain(|body| {
+            body.x > -width && body.x < width * 2.0 && body.y > -height && body.y < height * 2.0
+        });
+    }
+
+    fn draw(&self, context: &CanvasRenderingContext2d) {
+        let shown = self.detail.count(self.bodies.len());
+        self.bodies.iter().enumerate().for_each(|(i, body)| {
+            // Dropped masses come last but always stay visible
+            if body.mass > 1.0 {
+                let radius = 2.0 + body.mass.sqrt() * 0.3;
+                if self.detail.glow {
+                    context.set_fill_style_str("rgba(255, 220, 170, 0.08)");
+                    context.begin_path();
+                    context.arc(body.x, body.y, radius * 4.0, 0.0, std::f64::consts::PI * 2.0).unwrap();
+                    context.fill();
+                }
+
+                context.set_fill_style_str("rgba(255, 230, 190, 0.9)");
+                context.begin_path();
+                context.arc(body.x, body.y, radius, 0.0, std::f64::consts::PI * 2.0).unwrap();
+                context.fill();
+                return;
+            }
+            if i >= shown {
+                return;
+            }
+
+            // Faster stars burn brighter, which makes slingshots easy to follow
+            let speed = (body.vx * body.vx + body.vy * body.vy).sqrt();
+            let heat = (speed / 3.0).min(1.0);
+            let gray = (140.0 + heat * 100.0) as u32;
+            let alpha = 0.4 + heat * 0.5;
+            let color = format!("rgba({}, {}, {}, {})", gray, gray, gray, alpha);
+            context.set_fill_style_str(&color);
+            context.begin_path();
+            context.arc(body.x, body.y, 1.0 + heat, 0.0, std::f64::consts::PI * 2.0).unwrap();
+            context.fill();
+        });
+    }
+
+    fn star_count(&self) -> usize {
+        self.detail.count(self.bodies.len())
+    }
+
+    fn pointer(&mut self, event: Pointer) {
+        let Pointer::Down { x, y } = event else {
+            return;
+        };
+        self.bodies.push(Body {
+            x,
+            y,
+            vx: 0.0,
+            vy: 0.0,
+            ax: 0.0,
+            ay: 0.0,
+            mass: self.config.drop_mass,
+        });
+        self.compute_accelerations();
+    }
+
+    fn set_detail(&mut self, detail: Detail) {
+        self.detail = detail;
+    }
+}
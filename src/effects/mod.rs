@@ -0,0 +1,66 @@
+mod black_hole;
+mod galaxy;
+mod gravity;
+mod sky;
+mod spiral;
+mod tunnel;
+mod twinkle;
+mod warp;
+
+pub use black_hole::{BlackHole, BlackHoleConfig};
+pub use galaxy::{Galaxy, GalaxyConfig};
+pub use gravity::{Gravity, GravityConfig};
+pub use sky::{Sky, SkyConfig};
+pub use spiral::Spiral;
+pub use tunnel::{Tunnel, TunnelConfig};
+pub use twinkle::{Constellation, ConstellationShape, Twinkle};
+pub use warp::{MorphConfig, MorphTarget, Warp, WarpConfig};
+
+use crate::quality::Detail;
+use web_sys::CanvasRenderingContext2d;
+
+/// A star animation driven by the `Starfield` render loop.
+///
+/// `dt` is the time since the last frame measured in 60 Hz frames, so an
+/// effect written against a fixed 60 fps step keeps its speed on any display.
+pub trait Effect {
+    /// Color the frame is cleared to before anything is drawn.
+    fn background(&self) -> &'static str;
+    fn update(&mut self, dt: f64);
+    fn draw(&self, context: &CanvasRenderingContext2d);
+    /// Drawn straight onto the canvas after trails and bloom, for labels
+    /// that shouldn't smear.
+    fn draw_overlay(&self, _context: &CanvasRenderingContext2d) {}
+    /// Stars drawn per frame, shown in the debug overlay.
+    fn star_count(&self) -> usize;
+    /// Pointer input on the canvas, delivered before the next `update`.
+    fn pointer(&mut self, _event: Pointer) {}
+    /// Level of detail picked by the quality governor.
+    fn set_detail(&mut self, _detail: Detail) {}
+    /// Brightness relative to normal (1.0), for effects that support it.
+    fn set_brightness(&mut self, _brightness: f64) {}
+}
+
+/// Pointer input in canvas pixel coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pointer {
+    Down { x: f64, y: f64 },
+    Move { x: f64, y: f64 },
+    Leave,
+}
+
+/// Pulsing two-layer glow around a galactic core. `scale` multiplies the radii
+/// and `pulse` runs from 0.0 to 1.0.
+fn draw_core_glow(context: &CanvasRenderingContext2d, center_x: f64, center_y: f64, scale: f64, pulse: f64) {
+    let core_glow = format!("rgba(150, 150, 150, {})", 0.03 + pulse * 0.03);
+    context.set_fill_style_str(&core_glow);
+    context.begin_path();
+    context.arc(center_x, center_y, (60.0 + pulse * 15.0) * scale, 0.0, std::f64::consts::PI * 2.0).unwrap();
+    context.fill();
+
+    let inner_core = format!("rgba(200, 200, 200, {})", 0.05 + pulse * 0.05);
+    context.set_fill_style_str(&inner_core);
+    context.begin_path();
+    context.arc(center_x, center_y, (20.0 + pulse * 8.0) * scale, 0.0, std::f64::consts::PI * 2.0).unwrap();
+    context.fill();
+}
@@ -0,0 +1,224 @@
+use super::Effect;
+use crate::catalog::{color_from_index, Catalog};
+use crate::quality::Detail;
+use serde::{Deserialize, Serialize};
+use web_sys::CanvasRenderingContext2d;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkyConfig {
+    /// Observer latitude in degrees, north positive.
+    pub latitude: f64,
+    /// Observer longitude in degrees, east positive.
+    pub longitude: f64,
+    /// Unix time in milliseconds to show; `None` starts at the current time.
+    pub time: Option<f64>,
+    /// Simulated seconds per real second; 1.0 follows the real sky.
+    pub time_scale: f64,
+    pub constellation_lines: bool,
+    pub labels: bool,
+}
+
+impl Default for SkyConfig {
+    fn default() -> Self {
+        SkyConfig {
+            latitude: 51.48,
+            longitude: 0.0,
+            time: None,
+            time_scale: 1.0,
+            constellation_lines: true,
+            labels: true,
+        }
+    }
+}
+
+/// The real night sky seen from `latitude`/`longitude`, in a stereographic
+/// projection centered on the zenith with north up and the horizon touching
+/// the corners of the canvas.
+pub struct Sky {
+    config: SkyConfig,
+    catalog: Catalog,
+    center_x: f64,
+    center_y: f64,
+    radius: f64,
+    time: f64,
+    frames: f64,
+    detail: Detail,
+    // Catalog magnitudes from brightest to faintest, and the faintest drawn
+    magnitudes: Vec<f64>,
+    faintest: f64,
+}
+
+impl Sky {
+    pub fn new(config: SkyConfig, width: f64, height: f64) -> Self {
+        let time = config.time.unwrap_or_else(js_sys::Date::now);
+        let catalog = Catalog::bundled();
+        let mut magnitudes: Vec<f64> = catalog.stars.iter().map(|star| star.magnitude).collect();
+        magnitudes.sort_by(f64::total_cmp);
+        Sky {
+            config,
+            catalog,
+            center_x: width / 2.0,
+            center_y: height / 2.0,
+            radius: (width * width + height * height).sqrt() / 2.0,
+            time,
+            frames: 0.0,
+            detail: Detail::default(),
+            faintest: magnitudes.last().copied().unwrap_or(f64::INFINITY),
+            magnitudes,
+        }
+    }
+
+    /// Local sidereal time in degrees.
+    fn sidereal_time(&self) -> f64 {
+        let julian_date = self.time / 86_400_000.0 + 2_440_587.5;
+        let gmst = 280.460_618_37 + 360.985_647_366_29 * (julian_date - 2_451_545.0);
+        (gmst + self.config.longitude).rem_euclid(360.0)
+    }
+
+    /// Screen position and altitude (degrees) of an equatorial position, or
+    /// `None` below the horizon.
+    fn project(&self, ra: f64, dec: f64, sidereal_time: f64) -> Option<(f64, f64, f64)> {
+        let hour_angle = (sidereal_time - ra).to_radians();
+        let dec = dec.to_radians();
+        let lat = self.config.latitude.to_radians();
+
+        let altitude = (dec.sin() * lat.sin() + dec.cos() * lat.cos() * hour_angle.cos()).asin();
+        if altitude < 0.0 {
+            return None;
+        }
+        // Azimuth from north through east
+        let azimuth = (-dec.cos() * hour_angle.sin()).atan2(dec.sin() * lat.cos() - dec.cos() * lat.sin() * hour_angle.cos());
+
+        let zenith_distance = std::f64::consts::FRAC_PI_2 - altitude;
+        let r = self.radius * (zenith_distance / 2.0).tan();
+        // Looking up with north at the top puts east on the left
+        Some((
+            self.center_x - azimuth.sin() * r,
+            self.center_y - azimuth.cos() * r,
+            altitude.to_degrees(),
+        ))
+    }
+}
+
+impl Effect for Sky {
+    fn background(&self) -> &'static str {
+        "#05070a"
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.frames += dt;
+        self.time += dt * (1000.0 / 60.0) * self.config.time_scale;
+    }
+
+    fn draw(&self, context: &CanvasRenderingContext2d) {
+        let sidereal_time = self.sidereal_time();
+        let positions: Vec<Option<(f64, f64, f64)>> = self
+            .catalog
+            .stars
+            .iter()
+            .map(|star| self.project(star.ra, star.dec, sidereal_time))
+            .collect();
+
+        if self.config.constellation_lines {
+            context.set_stroke_style_str("rgba(110, 150, 190, 0.25)");
+            context.set_line_width(0.8);
+            context.begin_path();
+            for constellation in &self.catalog.constellations {
+                for &(a, b) in &constellation.segments {
+                    if let (Some((ax, ay, _)), Some((bx, by, _))) = (positions[a], positions[b]) {
+                        context.move_to(ax, ay);
+                        context.line_to(bx, by);
+                    }
+                }
+            }
+            context.stroke();
+        }
+
+        for (i, (star, position)) in self.catalog.stars.iter().zip(&positions).enumerate() {
+            let Some((x, y, altitude)) = *position else {
+                continue;
+            };
+            if star.magnitude > self.faintest {
+                continue;
+            }
+            let (r, g, b) = color_from_index(star.color_index);
+
+            // Brighter (lower magnitude) stars are bigger; the atmosphere
+            // dims and scintillates everything near the horizon
+            let size = (3.2 - star.magnitude * 0.55).max(0.6);
+            let extinction = (altitude / 15.0).clamp(0.2, 1.0);
+            let scintillation = 1.0 - (1.0 - extinction) * (self.frames * 0.2 + i as f64 * 1.7).sin().abs() * 0.5;
+            let alpha = ((1.0 - star.magnitude / 6.0).clamp(0.25, 1.0)) * extinction * scintillation;
+
+            let color = format!("rgba({}, {}, {}, {})", r, g, b, alpha);
+            context.set_fill_style_str(&color);
+            context.begin_path();
+            context.arc(x, y, size, 0.0, std::f64::consts::PI * 2.0).unwrap();
+            context.fill();
+
+            if self.detail.glow && star.magnitude < 1.5 {
+                let glow_color = format!("rgba({}, {}, {}, {})", r, g, b, alpha * 0.15);
+                context.set_fill_style_str(&glow_color);
+                context.begin_path();
+                context.arc(x, y, size * 3.0, 0.0, std::f64::consts::PI * 2.0).unwrap();
+                context.fill();
+            }
+
+            if self.config.labels && star.magnitude < 1.0 {
+                context.set_font("11px sans-serif");
+                context.set_fill_style_str("rgba(200, 210, 220, 0.45)");
+                context.fill_text(&star.name, x + size + 4.0, y + 4.0).unwrap();
+            }
+        }
+
+        if self.config.labels {
+            context.set_font("12px sans-serif");
+            context.set_fill_style_str("rgba(110, 150, 190, 0.5)");
+            context.set_text_align("center");
+            for constellation in &self.catalog.constellations {
+                // Label at the centroid of the figure's visible stars
+                let (mut sum_x, mut sum_y, mut count) = (0.0, 0.0, 0.0);
+                for &(a, b) in &constellation.segments {
+                    for (x, y, _) in [positions[a], positions[b]].into_iter().flatten() {
+                        sum_x += x;
+                        sum_y += y;
+                        count += 1.0;
+                    }
+                }
+                if count > 0.0 {
+                    context.fill_text(&constellation.name.to_uppercase(), sum_x / count, sum_y / count - 12.0).unwrap();
+                }
+            }
+
+            // Cardinal points, pulled in from the horizon to the canvas edges
+            for (label, azimuth) in [("N", 0.0f64), ("E", 90.0), ("S", 180.0), ("W", 270.0)] {
+                let x = self.center_x - azimuth.to_radians().sin() * self.radius;
+                let y = self.center_y - azimuth.to_radians().cos() * self.radius;
+                let x = x.clamp(16.0, self.center_x * 2.0 - 16.0);
+                let y = y.clamp(20.0, self.center_y * 2.0 - 12.0);
+                context.fill_text(label, x, y).unwrap();
+            }
+            context.set_text_align("start");
+        }
+    }
+
+    fn star_count(&self) -> usize {
+        let sidereal_time = self.sidereal_time();
+        self.catalog
+            .stars
+            .iter()
+            .filter(|star| star.magnitude <= self.faintest && self.project(star.ra, star.dec, sidereal_time).is_some())
+            .count()
+    }
+
+    fn set_detail(&mut self, detail: Detail) {
+        // Drop the faintest stars first
+        let shown = detail.count(self.magnitudes.len());
+        self.faintest = match shown {
+            0 => f64::NEG_INFINITY,
+            shown => self.magnitudes[shown - 1],
+        };
+        self.detail = detail;
+    }
+}
@@ -0,0 +1,143 @@
+use super::{draw_core_glow, Effect};
+use crate::optics::{Optics, OpticsConfig};
+use crate::quality::Detail;
+use crate::random;
+use rand::Rng;
+use web_sys::CanvasRenderingContext2d;
+
+pub struct Spiral {
+    center_x: f64,
+    center_y: f64,
+    max_dist: f64,
+    time: f64,
+    // The canvas keeps previous frames, so real trails replace the drawn ones
+    persistent: bool,
+    // Spikes and flare on the core
+    optics: Option<Optics>,
+    detail: Detail,
+    // angle, distance, size, speed, arm (which spiral arm)
+    stars: Vec<(f64, f64, f64, f64, u8)>,
+}
+
+impl Spiral {
+    pub fn new(persistent: bool, optics: Option<OpticsConfig>, width: f64, height: f64) -> Self {
+        let mut rng = random::rng();
+        let stars = (0..500)
+            .map(|_| {
+                let arm = rng.gen_range(0..4);
+                let base_angle = (arm as f64) * std::f64::consts::PI / 2.0;
+                let angle = base_angle + rng.gen_range(0.0..std::f64::consts::PI * 2.0);
+                let distance = rng.gen_range(20.0..(width.min(height) / 2.0));
+                (
+                    angle,
+                    distance,
+                    rng.gen_range(1.0..3.5),
+                    rng.gen_range(0.002..0.008),
+                    arm,
+                )
+            })
+            .collect();
+
+        Spiral {
+            center_x: width / 2.0,
+            center_y: height / 2.0,
+            max_dist: width.min(height) / 2.0,
+            time: 0.0,
+            persistent,
+            optics: optics.map(|config| Optics::new(config, width, height)),
+            detail: Detail::default(),
+            stars,
+        }
+    }
+}
+
+impl Effect for Spiral {
+    fn background(&self) -> &'static str {
+        "#060606"
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.time += 0.005 * dt;
+        self.stars.iter_mut().for_each(|star| {
+            star.0 += star.3 * dt;
+        });
+    }
+
+    fn draw(&self, context: &CanvasRenderingContext2d) {
+        let (center_x, center_y, max_dist) = (self.center_x, self.center_y, self.max_dist);
+
+        self.stars.iter().take(self.detail.count(self.stars.len())).for_each(|&(angle, distance, size, speed, arm)| {
+            // Spiral distortion - stars further out lag behind
+            let spiral_offset = distance / max_dist * 1.5;
+            let display_angle = angle + spiral_offset;
+
+            let x = center_x + display_angle.cos() * distance;
+            let y = center_y + display_angle.sin() * distance;
+
+            let dist_ratio = distance / max_dist;
+            let base_gray = match arm {
+                0 => 200,
+                1 => 170,
+                2 => 220,
+                _ => 150,
+            };
+            let gray = base_gray - (dist_ratio * 50.0) as u32;
+
+            // Main star
+            let alpha = 0.4 + (1.0 - dist_ratio) * 0.4;
+            let color = format!("rgba({}, {}, {}, {})", gray, gray, gray, alpha);
+            context.set_fill_style_str(&color);
+            context.begin_path();
+            context.arc(x, y, size * 0.8, 0.0, std::f64::consts::PI * 2.0).unwrap();
+            context.fill();
+
+            // Glow
+            if self.detail.glow {
+                let glow_color = format!("rgba({}, {}, {}, {})", gray, gray, gray, alpha * 0.15);
+                context.set_fill_style_str(&glow_color);
+                context.begin_path();
+                context.arc(x, y, size * 2.0, 0.0, std::f64::consts::PI * 2.0).unwrap();
+                context.fill();
+            }
+
+            if self.persistent {
+                return;
+            }
+
+            // Long flowing trail
+            let trail_length = 8;
+            for i in 1..=trail_length {
+                let trail_angle = display_angle - speed * (i as f64) * 5.0;
+                let trail_dist = distance + (i as f64) * 0.5;
+                let trail_x = center_x + trail_angle.cos() * trail_dist;
+                let trail_y = center_y + trail_angle.sin() * trail_dist;
+                let trail_alpha = alpha * (0.3 - (i as f64) * 0.035);
+                if trail_alpha > 0.0 {
+                    let trail_color = format!("rgba({}, {}, {}, {})", gray, gray, gray, trail_alpha);
+                    context.set_fill_style_str(&trail_color);
+                    context.begin_path();
+                    context.arc(trail_x, trail_y, size * (0.6 - (i as f64) * 0.04), 0.0, std::f64::consts::PI * 2.0).unwrap();
+                    context.fill();
+                }
+            }
+        });
+
+        // Center glow - pulsing
+        let pulse = (self.time * 2.0).sin() * 0.5 + 0.5;
+        draw_core_glow(context, center_x, center_y, 1.0, pulse);
+
+        if let Some(optics) = self.optics.as_ref().filter(|_| self.detail.glow) {
+            let brightness = 0.6 + pulse * 0.4;
+            optics.draw_star(context, center_x, center_y, 6.0, brightness);
+            optics.draw_flare(context, center_x, center_y, 6.0, brightness);
+        }
+    }
+
+    fn star_count(&self) -> usize {
+        self.detail.count(self.stars.len())
+    }
+
+    fn set_detail(&mut self, detail: Detail) {
+        self.detail = detail;
+    }
+}
@@ -0,0 +1,180 @@
+use super::warp::project;
+use super::Effect;
+use crate::quality::Detail;
+use crate::random;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use web_sys::CanvasRenderingContext2d;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TunnelConfig {
+    /// Tube radius as a fraction of the smaller canvas dimension.
+    pub radius: f64,
+    /// Extra rotation per unit of depth in radians, twisting the tube into a helix.
+    pub twist: f64,
+    /// How far the far end of the tube bends away from the center, 0.0 is straight.
+    pub curvature: f64,
+    /// Rotation of the whole tube in radians per frame.
+    pub spin: f64,
+    /// Forward speed in depth units per frame.
+    pub speed: f64,
+    /// Number of rings visible along the tube; 0 disables them.
+    pub rings: usize,
+}
+
+impl Default for TunnelConfig {
+    fn default() -> Self {
+        TunnelConfig {
+            radius: 0.45,
+            twist: 0.004,
+            curvature: 0.8,
+            spin: 0.004,
+            speed: 6.0,
+            rings: 14,
+        }
+    }
+}
+
+pub struct Tunnel {
+    config: TunnelConfig,
+    width: f64,
+    height: f64,
+    radius: f64,
+    time: f64,
+    rotation: f64,
+    ring_offset: f64,
+    // angle around the tube, z, size, gray
+    stars: Vec<(f64, f64, f64, u32)>,
+    detail: Detail,
+}
+
+impl Tunnel {
+    pub fn new(config: TunnelConfig, width: f64, height: f64) -> Self {
+        let mut rng = random::rng();
+        let stars = (0..400)
+            .map(|_| {
+                (
+                    rng.gen_range(0.0..std::f64::consts::PI * 2.0),
+                    rng.gen_range(1.0..width),
+                    rng.gen_range(0.8..1.6),
+                    rng.gen_range(150..230),
+                )
+            })
+            .collect();
+
+        Tunnel {
+            radius: width.min(height) * config.radius,
+            config,
+            width,
+            height,
+            time: 0.0,
+            rotation: 0.0,
+            ring_offset: 0.0,
+            stars,
+            detail: Detail::default(),
+        }
+    }
+
+    /// Center of the tube cross-section at depth `z` in world space. The bend
+    /// grows quadratically with depth and its direction slowly wanders.
+    fn axis(&self, z: f64) -> (f64, f64) {
+        let bend = self.config.curvature * (z / self.width).powi(2) * z * 2.0;
+        let direction = self.time * 0.004;
+        (direction.cos() * bend, (direction * 1.3).sin() * bend * 0.6)
+    }
+
+    fn world(&self, angle: f64, z: f64) -> (f64, f64) {
+        let (ax, ay) = self.axis(z);
+        let theta = angle + self.rotation + z * self.config.twist;
+        (ax + theta.cos() * self.radius, ay + theta.sin() * self.radius)
+    }
+}
+
+impl Effect for Tunnel {
+    fn background(&self) -> &'static str {
+        "#070707"
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.time += dt;
+        self.rotation += self.config.spin * dt;
+
+        let width = self.width;
+        let step = self.config.speed * dt;
+        // Depth runs from 1.0 up to the width and wraps both ways, so the
+        // tube refills when flying backwards too
+        let depth = (width - 1.0).max(1.0);
+        self.stars.iter_mut().for_each(|star| {
+            star.1 = 1.0 + (star.1 - step - 1.0).rem_euclid(depth);
+        });
+
+        if self.config.rings > 0 {
+            let spacing = width / self.config.rings as f64;
+            self.ring_offset = (self.ring_offset - step).rem_euclid(spacing);
+        }
+    }
+
+    fn draw(&self, context: &CanvasRenderingContext2d) {
+        let (width, height) = (self.width, self.height);
+
+        // Rings, far to near so the closer ones draw on top
+        if self.config.rings > 0 {
+            let spacing = width / self.config.rings as f64;
+            for i in (0..self.config.rings).rev() {
+                let z = self.ring_offset + i as f64 * spacing;
+                if z < 1.0 {
+                    continue;
+                }
+                let (ax, ay) = self.axis(z);
+                let (px, py, depth_factor) = project(ax, ay, z, width, height);
+                let ring_radius = self.radius * 128.0 / z;
+
+                let alpha = depth_factor * depth_factor * 0.25;
+                let ring_color = format!("rgba(170, 200, 210, {})", alpha);
+                context.set_stroke_style_str(&ring_color);
+                context.set_line_width(0.5 + depth_factor * 2.0);
+                context.begin_path();
+                context.arc(px, py, ring_radius, 0.0, std::f64::consts::PI * 2.0).unwrap();
+                context.stroke();
+            }
+        }
+
+        self.stars.iter().take(self.detail.count(self.stars.len())).for_each(|&(angle, z, size, gray)| {
+            let (x, y) = self.world(angle, z);
+            let (px, py, depth_factor) = project(x, y, z, width, height);
+            if px < 0.0 || px >= width || py < 0.0 || py >= height {
+                return;
+            }
+
+            // Streak back along the tube to where the star was a moment ago
+            let tail_z = (z + 10.0 + depth_factor * 30.0).min(width);
+            let (tx, ty) = self.world(angle, tail_z);
+            let (tail_px, tail_py, _) = project(tx, ty, tail_z, width, height);
+
+            let alpha = 0.15 + depth_factor * 0.6;
+            let streak_color = format!("rgba({}, {}, {}, {})", gray, gray, gray, alpha);
+            context.set_stroke_style_str(&streak_color);
+            context.set_line_width(size * (0.5 + depth_factor * 1.5));
+            context.begin_path();
+            context.move_to(tail_px, tail_py);
+            context.line_to(px, py);
+            context.stroke();
+
+            // Glow head
+            let glow_color = format!("rgba({}, {}, {}, {})", gray, gray, gray, alpha * 0.4);
+            context.set_fill_style_str(&glow_color);
+            context.begin_path();
+            context.arc(px, py, size * (1.0 + depth_factor * 2.0), 0.0, std::f64::consts::PI * 2.0).unwrap();
+            context.fill();
+        });
+    }
+
+    fn star_count(&self) -> usize {
+        self.detail.count(self.stars.len())
+    }
+
+    fn set_detail(&mut self, detail: Detail) {
+        self.detail = detail;
+    }
+}
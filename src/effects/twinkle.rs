@@ -0,0 +1,345 @@
+use super::{Effect, Pointer};
+use crate::optics::{Optics, OpticsConfig};
+use crate::quality::Detail;
+use crate::random;
+use crate::shape::{DistributionConfig, ImageDistribution, LuminanceMap};
+use crate::spatial::SpatialGrid;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use web_sys::CanvasRenderingContext2d;
+
+/// Which stars a constellation connects.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConstellationShape {
+    /// Star indices, joined in order.
+    Stars(Vec<usize>),
+    /// Polyline in normalized canvas coordinates (0.0..1.0 on both axes),
+    /// e.g. a traced logo; every vertex snaps to the nearest star.
+    Outline(Vec<(f64, f64)>),
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Constellation {
+    pub name: String,
+    pub shape: ConstellationShape,
+}
+
+impl Constellation {
+    pub fn new(name: &str, shape: ConstellationShape) -> Self {
+        Constellation {
+            name: name.to_string(),
+            shape,
+        }
+    }
+}
+
+// Frames for a constellation to draw itself in, hold, and fade out again
+const DRAW_FRAMES: f64 = 120.0;
+const HOLD_FRAMES: f64 = 360.0;
+const FADE_FRAMES: f64 = 120.0;
+
+pub struct Twinkle {
+    // x, y, base_size, phase, speed, base_gray
+    stars: Vec<(f64, f64, f64, f64, f64, f64)>,
+    width: f64,
+    height: f64,
+    grid: SpatialGrid,
+    // Figures as configured, kept to snap outlines again after a relayout
+    figures: Vec<Constellation>,
+    // name, star indices along the figure
+    constellations: Vec<(String, Vec<usize>)>,
+    distribution: Option<ImageDistribution>,
+    // Whether the layout is final, i.e. any distribution image has arrived
+    settled: bool,
+    // Fades the field in once it has its final layout
+    reveal: f64,
+    hovered: Option<usize>,
+    optics: Optics,
+    detail: Detail,
+    // Scales the twinkle; above 1.0 more stars reach full glow and spikes
+    brightness: f64,
+    time: f64,
+}
+
+impl Twinkle {
+    pub fn new(
+        constellations: Vec<Constellation>,
+        distribution: Option<DistributionConfig>,
+        optics: Option<OpticsConfig>,
+        width: f64,
+        height: f64,
+    ) -> Self {
+        let distribution = distribution.map(|config| ImageDistribution::load(config, width, height));
+        let mut twinkle = Twinkle {
+            stars: Vec::new(),
+            width,
+            height,
+            grid: SpatialGrid::new(Vec::new(), width, height, 40.0),
+            figures: constellations,
+            constellations: Vec::new(),
+            settled: distribution.is_none(),
+            reveal: if distribution.is_some() { 0.0 } else { 1.0 },
+            distribution,
+            hovered: None,
+            optics: Optics::new(optics.unwrap_or_default(), width, height),
+            detail: Detail::default(),
+            brightness: 1.0,
+            time: 0.0,
+        };
+        twinkle.layout(None);
+        twinkle
+    }
+
+    /// Scatters the stars, following `map` where one is given, and rebuilds
+    /// everything that refers to star positions.
+    fn layout(&mut self, map: Option<&LuminanceMap>) {
+        let mut rng = random::rng();
+        let (width, height) = (self.width, self.height);
+        let (count, strength) = match &self.distribution {
+            Some(distribution) => (distribution.config.stars, distribution.config.strength.clamp(0.0, 1.0)),
+            None => (350, 0.0),
+        };
+
+        self.stars = (0..count)
+            .map(|_| {
+                // Stars placed by the image also take their brightness from it
+                let placed = map.filter(|_| rng.gen_bool(strength)).and_then(|map| Some((map, map.sample(&mut rng)?)));
+                let (x, y, base_size, base_gray) = match placed {
+                    Some((map, (x, y))) => {
+                        let luminance = map.luminance(x, y);
+                        (x, y, 0.8 + luminance * 2.2, 120.0 + luminance * 100.0)
+                    }
+                    None => (
+                        rng.gen_range(0.0..width),
+                        rng.gen_range(0.0..height),
+                        rng.gen_range(0.8..3.0),
+                        rng.gen_range(120.0..200.0), // Gray range
+                    ),
+                };
+                (
+                    x,
+                    y,
+                    base_size,
+                    rng.gen_range(0.0..std::f64::consts::PI * 2.0),
+                    rng.gen_range(0.015..0.06),
+                    base_gray,
+                )
+            })
+            .collect();
+
+        self.grid = SpatialGrid::new(self.stars.iter().map(|star| (star.0, star.1)).collect(), width, height, 40.0);
+        self.hovered = None;
+
+        let star_count = self.stars.len();
+        let grid = &self.grid;
+        self.constellations = self
+            .figures
+            .iter()
+            .map(|constellation| {
+                let indices = match &constellation.shape {
+                    ConstellationShape::Stars(indices) => indices.iter().copied().filter(|&i| i < star_count).collect(),
+                    ConstellationShape::Outline(points) => {
+                        let mut indices: Vec<usize> = points
+                            .iter()
+                            .filter_map(|&(u, v)| grid.nearest(u * width, v * height, f64::INFINITY))
+                            .collect();
+                        indices.dedup();
+                        indices
+                    }
+                };
+                (constellation.name.clone(), indices)
+            })
+            .collect();
+    }
+
+    // How lit a star is at `phase` in radians, from 0.0 to 1.0
+    fn shine(&self, phase: f64) -> f64 {
+        ((phase.sin() + 1.0) / 2.0 * self.brightness).min(1.0)
+    }
+
+    fn constellation_of(&self, star: usize) -> Option<&str> {
+        self.constellations
+            .iter()
+            .find(|(_, indices)| indices.contains(&star))
+            .map(|(name, _)| name.as_str())
+    }
+
+    fn draw_constellations(&self, context: &CanvasRenderingContext2d) {
+        let cycle = DRAW_FRAMES + HOLD_FRAMES + FADE_FRAMES;
+        for (n, (_, indices)) in self.constellations.iter().enumerate() {
+            if indices.len() < 2 {
+                continue;
+            }
+            // Stagger the figures so they don't all animate in lockstep
+            let t = (self.time + n as f64 * cycle / 3.0) % cycle;
+            let progress = (t / DRAW_FRAMES).min(1.0);
+            let fade = ((cycle - t) / FADE_FRAMES).min(1.0);
+
+            let color = format!("rgba(0, 255, 200, {})", 0.35 * fade);
+            context.set_stroke_style_str(&color);
+            context.set_line_width(0.8);
+            context.begin_path();
+
+            // Segments draw in one after another
+            let drawn = progress * (indices.len() - 1) as f64;
+            for (i, pair) in indices.windows(2).enumerate() {
+                let fraction = (drawn - i as f64).clamp(0.0, 1.0);
+                if fraction <= 0.0 {
+                    break;
+                }
+                let (ax, ay) = (self.stars[pair[0]].0, self.stars[pair[0]].1);
+                let (bx, by) = (self.stars[pair[1]].0, self.stars[pair[1]].1);
+                context.move_to(ax, ay);
+                context.line_to(ax + (bx - ax) * fraction, ay + (by - ay) * fraction);
+            }
+            context.stroke();
+        }
+    }
+
+    fn draw_tooltip(&self, context: &CanvasRenderingContext2d, index: usize) {
+        let (x, y, base_size, phase, _, base_gray) = self.stars[index];
+        let brightness = self.shine(phase);
+        // Dims along with the stars it labels
+        let fade = self.brightness.min(1.0);
+
+        // Highlight ring
+        context.set_stroke_style_str(&format!("rgba(0, 255, 200, {})", 0.8 * fade));
+        context.set_line_width(1.0);
+        context.begin_path();
+        context.arc(x, y, base_size * 3.0 + 4.0, 0.0, std::f64::consts::PI * 2.0).unwrap();
+        context.stroke();
+
+        // Fake apparent magnitude from how bright the star is on average
+        let magnitude = 6.0 - (base_gray - 120.0) / 80.0 * 3.0 - base_size;
+        let title = match self.constellation_of(index) {
+            Some(name) => format!("{} · HS {:04}", name, index),
+            None => format!("HS {:04}", index),
+        };
+        let detail = format!("mag {:.1} · {}% lit", magnitude, (brightness * 100.0) as u32);
+
+        context.set_font("12px sans-serif");
+        let text_width = context.measure_text(&title).unwrap().width().max(context.measure_text(&detail).unwrap().width());
+        let (box_width, box_height) = (text_width + 20.0, 44.0);
+        // Keep the box on screen, flipping to the left near the right edge
+        let box_x = if x + 14.0 + box_width > self.width { x - 14.0 - box_width } else { x + 14.0 };
+        let box_y = (y - box_height / 2.0).max(4.0);
+
+        context.set_fill_style_str(&format!("rgba(10, 20, 20, {})", 0.85 * fade));
+        context.fill_rect(box_x, box_y, box_width, box_height);
+        context.set_stroke_style_str(&format!("rgba(0, 255, 200, {})", 0.3 * fade));
+        context.stroke_rect(box_x, box_y, box_width, box_height);
+
+        context.set_fill_style_str(&format!("rgba(255, 255, 255, {})", 0.95 * fade));
+        context.fill_text(&title, box_x + 10.0, box_y + 18.0).unwrap();
+        context.set_fill_style_str(&format!("rgba(255, 255, 255, {})", 0.6 * fade));
+        context.fill_text(&detail, box_x + 10.0, box_y + 34.0).unwrap();
+    }
+}
+
+impl Effect for Twinkle {
+    fn background(&self) -> &'static str {
+        "#080808"
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.time += dt;
+
+        if let Some(map) = self.distribution.as_ref().and_then(|distribution| distribution.take_map()) {
+            self.layout(Some(&map));
+            self.settled = true;
+            self.reveal = 0.0;
+        }
+        // Give the image a couple of seconds to arrive before showing the
+        // uniform fallback layout
+        if self.settled || self.time > 120.0 {
+            self.reveal = (self.reveal + dt / 60.0).min(1.0);
+        }
+        self.stars.iter_mut().for_each(|star| {
+            star.3 += star.4 * dt;
+        });
+    }
+
+    fn draw(&self, context: &CanvasRenderingContext2d) {
+        self.draw_constellations(context);
+
+        let visible = &self.stars[..self.detail.count(self.stars.len())];
+        visible.iter().for_each(|&(x, y, base_size, phase, _, base_gray)| {
+            let brightness = self.shine(phase);
+            let size = base_size * (0.3 + brightness * 0.5);
+
+            let gray = (base_gray + brightness * 60.0) as u32;
+            let alpha = (0.3 + brightness * 0.5) * self.reveal;
+            let color = format!("rgba({}, {}, {}, {})", gray, gray, gray, alpha);
+            context.set_fill_style_str(&color);
+
+            context.begin_path();
+            context.arc(x, y, size, 0.0, std::f64::consts::PI * 2.0).unwrap();
+            context.fill();
+
+            // Multi-layer glow
+            if self.detail.glow && brightness > 0.5 {
+                // Inner glow
+                let glow1 = format!("rgba({}, {}, {}, {})", gray, gray, gray, alpha * 0.2);
+                context.set_fill_style_str(&glow1);
+                context.begin_path();
+                context.arc(x, y, size * 2.0, 0.0, std::f64::consts::PI * 2.0).unwrap();
+                context.fill();
+
+                // Outer glow
+                let glow2 = format!("rgba({}, {}, {}, {})", gray, gray, gray, alpha * 0.1);
+                context.set_fill_style_str(&glow2);
+                context.begin_path();
+                context.arc(x, y, size * 3.0, 0.0, std::f64::consts::PI * 2.0).unwrap();
+                context.fill();
+            }
+
+            // Diffraction spikes on the brightest stars
+            if self.detail.glow && brightness > 0.85 {
+                let spark = (brightness - 0.85) / 0.15;
+                self.optics.draw_star(context, x, y, size, spark * self.reveal);
+            }
+        });
+
+        // Only the single brightest star flares, as it would dominate the lens
+        let brightest = visible
+            .iter()
+            .map(|&(x, y, base_size, phase, _, _)| (x, y, base_size, self.shine(phase)))
+            .filter(|&(_, _, _, brightness)| self.detail.glow && brightness > 0.85)
+            .max_by(|a, b| (a.2 * a.3).total_cmp(&(b.2 * b.3)));
+        if let Some((x, y, base_size, brightness)) = brightest {
+            let flare = (brightness - 0.85) / 0.15;
+            self.optics.draw_flare(context, x, y, base_size, flare * self.reveal);
+        }
+    }
+
+    fn draw_overlay(&self, context: &CanvasRenderingContext2d) {
+        if let Some(index) = self.hovered {
+            self.draw_tooltip(context, index);
+        }
+    }
+
+    fn pointer(&mut self, event: Pointer) {
+        self.hovered = match event {
+            // A tap selects just like hovering with a mouse
+            // Only the stars the level of detail leaves on screen
+            Pointer::Move { x, y } | Pointer::Down { x, y } => self.grid.nearest_among(x, y, 16.0, self.detail.count(self.stars.len())),
+            Pointer::Leave => None,
+        };
+    }
+
+    fn star_count(&self) -> usize {
+        self.detail.count(self.stars.len())
+    }
+
+    fn set_detail(&mut self, detail: Detail) {
+        self.detail = detail;
+        if self.hovered.is_some_and(|index| index >= detail.count(self.stars.len())) {
+            self.hovered = None;
+        }
+    }
+
+    fn set_brightness(&mut self, brightness: f64) {
+        self.brightness = brightness.max(0.0);
+    }
+}
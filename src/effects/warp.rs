@@ -0,0 +1,287 @@
+use super::Effect;
+use crate::quality::Detail;
+use crate::random;
+use crate::shape::{load_image, LuminanceMap};
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use web_sys::CanvasRenderingContext2d;
+
+/// Perspective projection shared by the depth-based effects. Depth runs from
+/// `width` (far) to 0.0 (at the viewer); returns the screen position and a
+/// depth factor that is 0.0 far away and approaches 1.0 up close.
+pub(super) fn project(x: f64, y: f64, z: f64, width: f64, height: f64) -> (f64, f64, f64) {
+    let scale = 128.0 / z;
+    let px = x * scale + width / 2.0;
+    let py = y * scale + height / 2.0;
+    (px, py, 1.0 - z / width)
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WarpConfig {
+    pub stars: usize,
+    /// Multiplies how fast the stars fly past.
+    pub speed: f64,
+    /// Streak length of the closest stars, in pixels.
+    pub streak: f64,
+}
+
+impl Default for WarpConfig {
+    fn default() -> Self {
+        WarpConfig {
+            stars: 250,
+            speed: 1.0,
+            streak: 30.0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MorphTarget {
+    Text(String),
+    /// URL of a PNG or SVG; bright pixels attract stars.
+    Image(String),
+}
+
+/// Gathers the warp stars into a shape, holds it, then releases them back
+/// into the field. Durations are in frames.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MorphConfig {
+    pub target: MorphTarget,
+    /// Stars in the field while morphing; text needs more than the usual 250
+    /// to stay legible. Used when it's more than `WarpConfig::stars`.
+    pub particles: usize,
+    /// Plain warp before the stars start gathering.
+    pub delay: f64,
+    pub coalesce: f64,
+    pub hold: f64,
+    pub scatter: f64,
+    pub repeat: bool,
+}
+
+impl MorphConfig {
+    pub fn text(text: &str) -> Self {
+        MorphConfig::new(MorphTarget::Text(text.to_string()))
+    }
+
+    pub fn image(url: &str) -> Self {
+        MorphConfig::new(MorphTarget::Image(url.to_string()))
+    }
+
+    fn new(target: MorphTarget) -> Self {
+        MorphConfig {
+            target,
+            particles: 900,
+            delay: 120.0,
+            coalesce: 150.0,
+            hold: 240.0,
+            scatter: 120.0,
+            repeat: true,
+        }
+    }
+}
+
+impl Default for MorphConfig {
+    // No target to gather into; presets are expected to name one
+    fn default() -> Self {
+        MorphConfig::text("")
+    }
+}
+
+struct Morph {
+    config: MorphConfig,
+    // Empty until the target has been rendered; images load asynchronously
+    targets: Rc<RefCell<Vec<(f64, f64)>>>,
+    time: f64,
+}
+
+impl Morph {
+    fn new(config: MorphConfig, width: f64, height: f64) -> Self {
+        let targets = Rc::new(RefCell::new(Vec::new()));
+        let count = config.particles;
+
+        match &config.target {
+            MorphTarget::Text(text) => {
+                let map = LuminanceMap::from_text(text, width, height);
+                *targets.borrow_mut() = sample_targets(&map, count);
+            }
+            MorphTarget::Image(url) => {
+                let targets = targets.clone();
+                load_image(url, move |image| {
+                    let map = LuminanceMap::from_image(image, width, height, false);
+                    *targets.borrow_mut() = sample_targets(&map, count);
+                });
+            }
+        }
+
+        Morph {
+            config,
+            targets,
+            time: 0.0,
+        }
+    }
+
+    fn cycle(&self) -> f64 {
+        self.config.delay + self.config.coalesce + self.config.hold + self.config.scatter
+    }
+
+    /// How far the stars are pulled onto the shape: 0.0 is free warp, 1.0 is
+    /// sitting on the target points.
+    fn weight(&self) -> f64 {
+        let ease = |t: f64| t * t * (3.0 - 2.0 * t);
+        let t = self.time - self.config.delay;
+        if t < 0.0 {
+            0.0
+        } else if t < self.config.coalesce {
+            ease(t / self.config.coalesce)
+        } else if t < self.config.coalesce + self.config.hold {
+            1.0
+        } else if t < self.config.coalesce + self.config.hold + self.config.scatter {
+            1.0 - ease((t - self.config.coalesce - self.config.hold) / self.config.scatter)
+        } else {
+            0.0
+        }
+    }
+}
+
+fn sample_targets(map: &LuminanceMap, count: usize) -> Vec<(f64, f64)> {
+    let mut rng = random::rng();
+    (0..count).filter_map(|_| map.sample(&mut rng)).collect()
+}
+
+pub struct Warp {
+    config: WarpConfig,
+    rng: StdRng,
+    width: f64,
+    height: f64,
+    // x, y, z, variant
+    stars: Vec<(f64, f64, f64, u8)>,
+    morph: Option<Morph>,
+    detail: Detail,
+}
+
+impl Warp {
+    pub fn new(config: WarpConfig, morph: Option<MorphConfig>, width: f64, height: f64) -> Self {
+        let mut rng = random::rng();
+        let count = morph.as_ref().map_or(config.stars, |morph| morph.particles.max(config.stars));
+        let stars = (0..count)
+            .map(|_| {
+                (
+                    rng.gen_range(-width / 2.0..width / 2.0),
+                    rng.gen_range(-height / 2.0..height / 2.0),
+                    rng.gen_range(1.0..width),
+                    rng.gen_range(0..3),
+                )
+            })
+            .collect();
+
+        Warp {
+            config,
+            rng,
+            width,
+            height,
+            stars,
+            morph: morph.map(|config| Morph::new(config, width, height)),
+            detail: Detail::default(),
+        }
+    }
+}
+
+impl Effect for Warp {
+    fn background(&self) -> &'static str {
+        "#0a0a0a"
+    }
+
+    fn update(&mut self, dt: f64) {
+        let (width, height) = (self.width, self.height);
+        let rng = &mut self.rng;
+
+        // Stars slow to a stop while they form the shape
+        let mut speed = self.config.speed;
+        if let Some(morph) = self.morph.as_mut() {
+            if !morph.targets.borrow().is_empty() {
+                morph.time += dt;
+                if morph.config.repeat && morph.time > morph.cycle() {
+                    morph.time = 0.0;
+                }
+            }
+            speed *= 1.0 - morph.weight();
+        }
+
+        self.stars.iter_mut().for_each(|star| {
+            let depth_factor = 1.0 - star.2 / width;
+            star.2 -= (3.0 + depth_factor * 4.0) * dt * speed;
+
+            if star.2 <= 0.0 {
+                *star = (
+                    rng.gen_range(-width / 2.0..width / 2.0),
+                    rng.gen_range(-height / 2.0..height / 2.0),
+                    width,
+                    rng.gen_range(0..3),
+                );
+            }
+        });
+    }
+
+    fn draw(&self, context: &CanvasRenderingContext2d) {
+        let (width, height) = (self.width, self.height);
+        let weight = self.morph.as_ref().map_or(0.0, |morph| morph.weight());
+        let targets = self.morph.as_ref().map(|morph| morph.targets.borrow());
+        let targets: &[(f64, f64)] = targets.as_deref().map_or(&[], |targets| targets.as_slice());
+
+        self.stars.iter().take(self.detail.count(self.stars.len())).enumerate().for_each(|(i, &(x, y, z, variant))| {
+            let (mut px, mut py, depth_factor) = project(x, y, z, width, height);
+
+            // Pull towards the star's point on the shape
+            if weight > 0.0 && !targets.is_empty() {
+                let (tx, ty) = targets[i % targets.len()];
+                px += (tx - px) * weight;
+                py += (ty - py) * weight;
+            }
+
+            if px >= 0.0 && px < width && py >= 0.0 && py < height {
+                let length = self.config.streak * depth_factor * (1.0 - weight);
+                let dx = x / z * length;
+                let dy = y / z * length;
+
+                let gray = match variant {
+                    0 => 180,
+                    1 => 220,
+                    _ => 150,
+                };
+
+                let alpha = 0.2 + depth_factor * 0.5;
+                let alpha = alpha + (0.85 - alpha) * weight;
+                let streak_color = format!("rgba({}, {}, {}, {})", gray, gray, gray, alpha);
+                context.set_stroke_style_str(&streak_color);
+                context.set_line_width(0.5 + depth_factor * 1.5);
+                context.begin_path();
+                context.move_to(px, py);
+                context.line_to(px + dx, py + dy);
+                context.stroke();
+
+                // Glow head
+                let glow_alpha = alpha * 0.4;
+                let glow_color = format!("rgba({}, {}, {}, {})", gray, gray, gray, glow_alpha);
+                context.set_fill_style_str(&glow_color);
+                context.begin_path();
+                let head = 1.5 + depth_factor * 2.0;
+                context.arc(px + dx, py + dy, head + (1.2 - head) * weight, 0.0, std::f64::consts::PI * 2.0).unwrap();
+                context.fill();
+            }
+        });
+    }
+
+    fn star_count(&self) -> usize {
+        self.detail.count(self.stars.len())
+    }
+
+    fn set_detail(&mut self, detail: Detail) {
+        self.detail = detail;
+    }
+}
@@ -0,0 +1,556 @@
+//! A hidden Asteroids game, started with the Konami code on the starfield in
+//! view and played over its stars. Arrows or WASD steer and thrust, Space
+//! fires, Enter starts over and Escape hands the canvas back to the effect.
+//! On touch screens the ship chases the finger and fires while it's held.
+
+use crate::random;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, KeyboardEvent};
+
+const KONAMI: [&str; 10] = [
+    "ArrowUp", "ArrowUp", "ArrowDown", "ArrowDown", "ArrowLeft", "ArrowRight", "ArrowLeft", "ArrowRight", "KeyB", "KeyA",
+];
+
+const HIGH_SCORE_KEY: &str = "hyperspace-asteroids-high-score";
+
+// Rock radii from a fresh rock down to the smallest piece, and their points
+const ROCK_SIZES: [(f64, u32); 3] = [(44.0, 20), (24.0, 50), (12.0, 100)];
+
+const SHIP_RADIUS: f64 = 12.0;
+const TURN_RATE: f64 = 0.08;
+const THRUST: f64 = 0.15;
+const MAX_SPEED: f64 = 8.0;
+const BULLET_SPEED: f64 = 9.0;
+const BULLET_LIFETIME: f64 = 55.0;
+const FIRE_COOLDOWN: f64 = 10.0;
+const RESPAWN_FRAMES: f64 = 90.0;
+const INVULNERABLE_FRAMES: f64 = 150.0;
+const HINT_FRAMES: f64 = 240.0;
+// Side of the close box in the top right corner, for touch screens
+const CLOSE_SIZE: f64 = 44.0;
+
+/// The controls held down this frame.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Input {
+    left: bool,
+    right: bool,
+    thrust: bool,
+    fire: bool,
+    restart: bool,
+    exit: bool,
+    /// Where a finger or the mouse button is held on the canvas.
+    touch: Option<(f64, f64)>,
+    /// Where the canvas was pressed since the last frame.
+    tap: Option<(f64, f64)>,
+}
+
+#[derive(Default)]
+struct ControlState {
+    // Keys of the Konami code typed so far
+    progress: usize,
+    requested: bool,
+    playing: bool,
+    input: Input,
+}
+
+/// Keyboard and pointer state for the game, shared between the window's key
+/// listener, the canvas's pointer handlers and the render loop.
+#[derive(Clone, Default)]
+pub(crate) struct GameControls(Rc<RefCell<ControlState>>);
+
+impl GameControls {
+    /// Starts listening to the keyboard; the returned function stops it.
+    pub fn listen(&self) -> impl FnOnce() {
+        let key = |down: bool| {
+            let controls = self.clone();
+            Closure::<dyn FnMut(KeyboardEvent)>::new(move |e: KeyboardEvent| controls.key(&e, down))
+        };
+        let window = web_sys::window().unwrap();
+        let listeners = [("keydown", key(true)), ("keyup", key(false))];
+        for (event, closure) in &listeners {
+            window.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref()).unwrap();
+        }
+        move || {
+            for (event, closure) in &listeners {
+                window.remove_event_listener_with_callback(event, closure.as_ref().unchecked_ref()).unwrap();
+            }
+        }
+    }
+
+    fn key(&self, e: &KeyboardEvent, down: bool) {
+        let state = &mut *self.0.borrow_mut();
+        let code = e.code();
+        if !state.playing {
+            if down {
+                state.progress = if code == KONAMI[state.progress] {
+                    state.progress + 1
+                } else if code == KONAMI[0] {
+                    // A repeated first key keeps the start of the code
+                    1
+                } else {
+                    0
+                };
+                if state.progress == KONAMI.len() {
+                    state.progress = 0;
+                    state.requested = true;
+                }
+            }
+            return;
+        }
+
+        let input = &mut state.input;
+        let held = match code.as_str() {
+            "ArrowLeft" | "KeyA" => &mut input.left,
+            "ArrowRight" | "KeyD" => &mut input.right,
+            "ArrowUp" | "KeyW" => &mut input.thrust,
+            "Space" => &mut input.fire,
+            "Enter" => &mut input.restart,
+            "Escape" => &mut input.exit,
+            _ => return,
+        };
+        *held = down;
+        // Arrows and Space would scroll the page otherwise
+        e.prevent_default();
+    }
+
+    /// Whether the code was typed since the last call.
+    pub fn take_request(&self) -> bool {
+        std::mem::take(&mut self.0.borrow_mut().requested)
+    }
+
+    pub fn set_playing(&self, playing: bool) {
+        let mut state = self.0.borrow_mut();
+        state.playing = playing;
+        state.input = Input::default();
+    }
+
+    pub fn press(&self, x: f64, y: f64) {
+        let input = &mut self.0.borrow_mut().input;
+        input.touch = Some((x, y));
+        input.tap = Some((x, y));
+    }
+
+    pub fn drag(&self, x: f64, y: f64) {
+        let input = &mut self.0.borrow_mut().input;
+        if input.touch.is_some() {
+            input.touch = Some((x, y));
+        }
+    }
+
+    pub fn release(&self) {
+        self.0.borrow_mut().input.touch = None;
+    }
+
+    /// The controls for this frame. Taps and one-off keys count once.
+    pub fn input(&self) -> Input {
+        let input = &mut self.0.borrow_mut().input;
+        let current = *input;
+        input.tap = None;
+        input.restart = false;
+        input.exit = false;
+        current
+    }
+}
+
+struct Ship {
+    x: f64,
+    y: f64,
+    vx: f64,
+    vy: f64,
+    // Heading in radians, 0 pointing right
+    angle: f64,
+}
+
+struct Rock {
+    x: f64,
+    y: f64,
+    vx: f64,
+    vy: f64,
+    // Index into ROCK_SIZES
+    size: usize,
+    rotation: f64,
+    spin: f64,
+    // Radius of each outline point relative to the size
+    outline: Vec<f64>,
+}
+
+impl Rock {
+    fn radius(&self) -> f64 {
+        ROCK_SIZES[self.size].0
+    }
+}
+
+pub(crate) struct Asteroids {
+    rng: StdRng,
+    width: f64,
+    height: f64,
+    ship: Option<Ship>,
+    rocks: Vec<Rock>,
+    // x, y, vx, vy, age in frames
+    bullets: Vec<(f64, f64, f64, f64, f64)>,
+    // x, y, vx, vy, age in frames
+    debris: Vec<(f64, f64, f64, f64, f64)>,
+    score: u32,
+    high_score: u32,
+    lives: u32,
+    wave: u32,
+    cooldown: f64,
+    // Frames until the ship comes back, and it stays safe from rocks after
+    respawn: f64,
+    invulnerable: f64,
+    time: f64,
+    closed: bool,
+}
+
+impl Asteroids {
+    pub fn new(width: f64, height: f64) -> Self {
+        let mut game = Asteroids {
+            rng: random::rng(),
+            width,
+            height,
+            ship: None,
+            rocks: Vec::new(),
+            bullets: Vec::new(),
+            debris: Vec::new(),
+            score: 0,
+            high_score: load_high_score(),
+            lives: 3,
+            wave: 0,
+            cooldown: 0.0,
+            respawn: 0.0,
+            invulnerable: 0.0,
+            time: 0.0,
+            closed: false,
+        };
+        game.spawn_ship();
+        game.next_wave();
+        game
+    }
+
+    /// Whether the player has left the game.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Leaves the game, keeping the score as if the player had.
+    pub fn close(&mut self) {
+        self.record_score();
+        self.closed = true;
+    }
+
+    fn is_over(&self) -> bool {
+        self.lives == 0 && self.ship.is_none()
+    }
+
+    fn spawn_ship(&mut self) {
+        self.ship = Some(Ship {
+            x: self.width / 2.0,
+            y: self.height / 2.0,
+            vx: 0.0,
+            vy: 0.0,
+            angle: -PI / 2.0,
+        });
+        self.invulnerable = INVULNERABLE_FRAMES;
+    }
+
+    fn next_wave(&mut self) {
+        self.wave += 1;
+        for _ in 0..(3 + self.wave).min(10) {
+            // Along the edges, away from the ship in the middle
+            let (x, y) = if self.rng.gen_bool(0.5) {
+                (self.rng.gen_range(0.0..self.width), 0.0)
+            } else {
+                (0.0, self.rng.gen_range(0.0..self.height))
+            };
+            self.add_rock(x, y, 0);
+        }
+    }
+
+    fn add_rock(&mut self, x: f64, y: f64, size: usize) {
+        let angle = self.rng.gen_range(0.0..PI * 2.0);
+        let speed = self.rng.gen_range(0.5..1.4) * (1.0 + size as f64 * 0.4) * (1.0 + self.wave as f64 * 0.08);
+        let outline = (0..11).map(|_| self.rng.gen_range(0.75..1.15)).collect();
+        let spin = self.rng.gen_range(-0.03..0.03);
+        self.rocks.push(Rock {
+            x,
+            y,
+            vx: angle.cos() * speed,
+            vy: angle.sin() * speed,
+            size,
+            rotation: 0.0,
+            spin,
+            outline,
+        });
+    }
+
+    fn explode(&mut self, x: f64, y: f64, count: usize) {
+        for _ in 0..count {
+            let angle = self.rng.gen_range(0.0..PI * 2.0);
+            let speed = self.rng.gen_range(0.5..3.0);
+            self.debris.push((x, y, angle.cos() * speed, angle.sin() * speed, 0.0));
+        }
+    }
+
+    pub fn update(&mut self, dt: f64, input: Input) {
+        self.time += dt;
+        if input.exit || input.tap.is_some_and(|(x, y)| x > self.width - CLOSE_SIZE && y < CLOSE_SIZE) {
+            self.close();
+            return;
+        }
+        if self.is_over() && (input.restart || input.tap.is_some()) {
+            *self = Asteroids::new(self.width, self.height);
+            return;
+        }
+
+        let (width, height) = (self.width, self.height);
+        let wrap = move |x: f64, y: f64| (x.rem_euclid(width), y.rem_euclid(height));
+
+        self.cooldown -= dt;
+        self.invulnerable -= dt;
+        if let Some(ship) = self.ship.as_mut() {
+            let (mut turn, mut thrust, mut fire) = (0.0, input.thrust, input.fire);
+            if input.left {
+                turn -= 1.0;
+            }
+            if input.right {
+                turn += 1.0;
+            }
+            // Touch: turn towards the finger, fly at it when it's far off and
+            // keep firing
+            if let Some((x, y)) = input.touch {
+                let (dx, dy) = (x - ship.x, y - ship.y);
+                let off = ((dy.atan2(dx) - ship.angle + PI).rem_euclid(PI * 2.0)) - PI;
+                turn = (off / (TURN_RATE * dt.max(1e-3))).clamp(-1.0, 1.0);
+                thrust = dx.hypot(dy) > 120.0;
+                fire = true;
+            }
+
+            ship.angle += turn * TURN_RATE * dt;
+            if thrust {
+                ship.vx += ship.angle.cos() * THRUST * dt;
+                ship.vy += ship.angle.sin() * THRUST * dt;
+            }
+            let friction = 0.99f64.powf(dt);
+            let speed = ship.vx.hypot(ship.vy);
+            let limit = if speed > MAX_SPEED { MAX_SPEED / speed } else { 1.0 };
+            ship.vx *= friction * limit;
+            ship.vy *= friction * limit;
+            (ship.x, ship.y) = wrap(ship.x + ship.vx * dt, ship.y + ship.vy * dt);
+
+            if fire && self.cooldown <= 0.0 {
+                self.cooldown = FIRE_COOLDOWN;
+                let (cos, sin) = (ship.angle.cos(), ship.angle.sin());
+                self.bullets.push((
+                    ship.x + cos * SHIP_RADIUS,
+                    ship.y + sin * SHIP_RADIUS,
+                    cos * BULLET_SPEED + ship.vx,
+                    sin * BULLET_SPEED + ship.vy,
+                    0.0,
+                ));
+            }
+        } else if self.lives > 0 {
+            self.respawn -= dt;
+            if self.respawn <= 0.0 {
+                self.spawn_ship();
+            }
+        }
+
+        for bullet in &mut self.bullets {
+            (bullet.0, bullet.1) = wrap(bullet.0 + bullet.2 * dt, bullet.1 + bullet.3 * dt);
+            bullet.4 += dt;
+        }
+        self.bullets.retain(|bullet| bullet.4 < BULLET_LIFETIME);
+        for rock in &mut self.rocks {
+            (rock.x, rock.y) = wrap(rock.x + rock.vx * dt, rock.y + rock.vy * dt);
+            rock.rotation += rock.spin * dt;
+        }
+        for piece in &mut self.debris {
+            piece.0 += piece.2 * dt;
+            piece.1 += piece.3 * dt;
+            piece.4 += dt;
+        }
+        self.debris.retain(|piece| piece.4 < 40.0);
+
+        self.collide();
+        if self.rocks.is_empty() {
+            self.next_wave();
+        }
+    }
+
+    fn collide(&mut self) {
+        // Bullets against rocks; a hit rock splits into two smaller ones
+        let rocks = &self.rocks;
+        let mut hit = vec![false; rocks.len()];
+        self.bullets.retain(|&(x, y, ..)| {
+            let struck = (0..rocks.len())
+                .find(|&i| !hit[i] && (rocks[i].x - x).hypot(rocks[i].y - y) < rocks[i].radius());
+            if let Some(i) = struck {
+                hit[i] = true;
+            }
+            struck.is_none()
+        });
+        let mut hit = hit.into_iter();
+        let (broken, kept): (Vec<Rock>, _) = std::mem::take(&mut self.rocks)
+            .into_iter()
+            .partition(|_| hit.next().unwrap());
+        self.rocks = kept;
+        for Rock { x, y, size, .. } in broken {
+            self.score += ROCK_SIZES[size].1;
+            self.explode(x, y, 8);
+            if size + 1 < ROCK_SIZES.len() {
+                self.add_rock(x, y, size + 1);
+                self.add_rock(x, y, size + 1);
+            }
+        }
+
+        // Rocks against the ship
+        let Some(ship) = self.ship.as_ref() else {
+            return;
+        };
+        if self.invulnerable > 0.0 {
+            return;
+        }
+        let crashed = self
+            .rocks
+            .iter()
+            .any(|rock| (rock.x - ship.x).hypot(rock.y - ship.y) < rock.radius() * 0.9 + SHIP_RADIUS * 0.7);
+        if crashed {
+            let (x, y) = (ship.x, ship.y);
+            self.ship = None;
+            self.explode(x, y, 24);
+            self.lives -= 1;
+            self.respawn = RESPAWN_FRAMES;
+            if self.lives == 0 {
+                self.record_score();
+            }
+        }
+    }
+
+    fn record_score(&mut self) {
+        if self.score > self.high_score {
+            self.high_score = self.score;
+            save_high_score(self.score);
+        }
+    }
+
+    pub fn draw(&self, context: &CanvasRenderingContext2d) {
+        // Dim the stars a little so the game reads on top of them
+        context.set_fill_style_str("rgba(0, 0, 0, 0.35)");
+        context.fill_rect(0.0, 0.0, self.width, self.height);
+
+        context.set_stroke_style_str("rgba(255, 255, 255, 0.9)");
+        context.set_line_width(1.5);
+        context.set_line_join("round");
+        for rock in &self.rocks {
+            context.begin_path();
+            for (i, scale) in rock.outline.iter().enumerate() {
+                let angle = rock.rotation + i as f64 / rock.outline.len() as f64 * PI * 2.0;
+                let radius = rock.radius() * scale;
+                let (px, py) = (rock.x + angle.cos() * radius, rock.y + angle.sin() * radius);
+                if i == 0 {
+                    context.move_to(px, py);
+                } else {
+                    context.line_to(px, py);
+                }
+            }
+            context.close_path();
+            context.stroke();
+        }
+
+        if let Some(ship) = self.ship.as_ref() {
+            // Blinks while it can't be hit
+            if self.invulnerable <= 0.0 || (self.time / 12.0).fract() < 0.5 {
+                self.draw_ship(context, ship.x, ship.y, ship.angle, SHIP_RADIUS);
+            }
+        }
+
+        context.set_fill_style_str("rgba(255, 255, 255, 0.95)");
+        for (x, y, ..) in &self.bullets {
+            context.fill_rect(x - 1.5, y - 1.5, 3.0, 3.0);
+        }
+        for (x, y, _, _, age) in &self.debris {
+            context.set_fill_style_str(&format!("rgba(255, 200, 150, {})", 1.0 - age / 40.0));
+            context.fill_rect(x - 1.0, y - 1.0, 2.0, 2.0);
+        }
+
+        self.draw_hud(context);
+        context.set_line_join("miter");
+    }
+
+    fn draw_ship(&self, context: &CanvasRenderingContext2d, x: f64, y: f64, angle: f64, radius: f64) {
+        let point = |turn: f64, length: f64| (x + (angle + turn).cos() * length, y + (angle + turn).sin() * length);
+        let (nose, left, right) = (point(0.0, radius), point(2.5, radius), point(-2.5, radius));
+        context.begin_path();
+        context.move_to(nose.0, nose.1);
+        context.line_to(left.0, left.1);
+        context.line_to(right.0, right.1);
+        context.close_path();
+        context.stroke();
+    }
+
+    fn draw_hud(&self, context: &CanvasRenderingContext2d) {
+        context.set_fill_style_str("rgba(255, 255, 255, 0.9)");
+        context.set_font("16px monospace");
+        context.set_text_align("left");
+        context.fill_text(&format!("SCORE {}", self.score), 16.0, 28.0).unwrap();
+        context.fill_text(&format!("HIGH  {}", self.high_score.max(self.score)), 16.0, 50.0).unwrap();
+        for life in 0..self.lives.saturating_sub(self.ship.is_some() as u32) {
+            self.draw_ship(context, 24.0 + life as f64 * 20.0, 74.0, -PI / 2.0, 7.0);
+        }
+
+        // Close box
+        let (x, y) = (self.width - CLOSE_SIZE / 2.0, CLOSE_SIZE / 2.0);
+        context.begin_path();
+        context.move_to(x - 8.0, y - 8.0);
+        context.line_to(x + 8.0, y + 8.0);
+        context.move_to(x + 8.0, y - 8.0);
+        context.line_to(x - 8.0, y + 8.0);
+        context.stroke();
+
+        context.set_text_align("center");
+        let (center_x, center_y) = (self.width / 2.0, self.height / 2.0);
+        if self.is_over() {
+            context.set_font("32px monospace");
+            context.fill_text("GAME OVER", center_x, center_y).unwrap();
+            context.set_font("14px monospace");
+            context.fill_text("Enter or tap to play again · Esc to leave", center_x, center_y + 32.0).unwrap();
+        } else if self.time < HINT_FRAMES {
+            // Fades out over the last second
+            context.set_global_alpha(((HINT_FRAMES - self.time) / 60.0).min(1.0));
+            context.set_font("14px monospace");
+            context
+                .fill_text("← → turn · ↑ thrust · Space fire · Esc leave", center_x, self.height - 60.0)
+                .unwrap();
+            context
+                .fill_text("or hold a finger where to fly", center_x, self.height - 40.0)
+                .unwrap();
+            context.set_global_alpha(1.0);
+        }
+        context.set_text_align("start");
+    }
+}
+
+fn load_high_score() -> u32 {
+    web_sys::window()
+        .unwrap()
+        .local_storage()
+        .ok()
+        .flatten()
+        .and_then(|storage| storage.get_item(HIGH_SCORE_KEY).ok().flatten())
+        .and_then(|score| score.parse().ok())
+        .unwrap_or(0)
+}
+
+fn save_high_score(score: u32) {
+    // Storage can be unavailable, e.g. in private windows; the score then
+    // only lasts until the page closes
+    if let Some(storage) = web_sys::window().unwrap().local_storage().ok().flatten() {
+        let _ = storage.set_item(HIGH_SCORE_KEY, &score.to_string());
+    }
+}
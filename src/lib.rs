@@ -0,0 +1,27 @@
+pub mod bloom;
+mod canvas;
+pub mod capture;
+pub mod catalog;
+pub mod editor;
+pub mod effects;
+mod game;
+pub mod nebula;
+mod noise;
+pub mod optics;
+mod overlay;
+pub mod preset;
+pub mod quality;
+mod random;
+pub mod reactive;
+pub mod screensaver;
+pub mod shape;
+mod shooting_stars;
+pub mod soundtrack;
+mod spatial;
+pub mod starfield;
+pub mod timeline;
+mod trails;
+pub mod tween;
+pub mod url;
+
+pub use starfield::{StarEffect, Starfield, StarfieldProps};
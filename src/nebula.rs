@@ -0,0 +1,192 @@
+use crate::canvas::{context_2d, create_canvas};
+use crate::noise::Perlin;
+use crate::random;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::Clamped;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
+
+/// One colored gas layer of the nebula.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct NebulaLayer {
+    pub color: (u8, u8, u8),
+    /// Noise frequency across the texture; larger values give smaller clouds.
+    pub scale: f64,
+    /// Noise level below which the layer is fully transparent.
+    pub threshold: f64,
+    /// Domain warp strength; 0.0 gives plain fBm blobs.
+    pub warp: f64,
+    pub opacity: f64,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NebulaConfig {
+    pub layers: Vec<NebulaLayer>,
+    /// Texture resolution relative to the canvas. The texture is upscaled when
+    /// drawn, so low values are cheap and still look smooth.
+    pub resolution: f64,
+    pub octaves: u32,
+    /// Rotation speed of the drift in radians per frame.
+    pub drift: f64,
+}
+
+impl Default for NebulaConfig {
+    fn default() -> Self {
+        NebulaConfig {
+            layers: vec![
+                NebulaLayer {
+                    color: (0, 90, 100),
+                    scale: 2.5,
+                    threshold: -0.1,
+                    warp: 4.0,
+                    opacity: 0.35,
+                },
+                NebulaLayer {
+                    color: (20, 50, 120),
+                    scale: 3.5,
+                    threshold: 0.0,
+                    warp: 3.0,
+                    opacity: 0.3,
+                },
+                NebulaLayer {
+                    color: (80, 30, 100),
+                    scale: 5.0,
+                    threshold: 0.1,
+                    warp: 2.0,
+                    opacity: 0.25,
+                },
+            ],
+            resolution: 0.2,
+            octaves: 5,
+            drift: 0.0003,
+        }
+    }
+}
+
+type Rgb = (u8, u8, u8);
+
+// name, layer colors from the back layer to the front one
+const PALETTES: [(&str, [Rgb; 3]); 4] = [
+    ("teal", [(0, 90, 100), (20, 50, 120), (80, 30, 100)]),
+    ("ember", [(120, 40, 10), (110, 70, 20), (70, 20, 40)]),
+    ("violet", [(70, 20, 110), (40, 30, 120), (110, 30, 90)]),
+    ("aurora", [(10, 110, 60), (0, 80, 100), (60, 30, 110)]),
+];
+
+impl NebulaConfig {
+    /// The default nebula in one of the named palettes: "teal" (the
+    /// default colors), "ember", "violet" or "aurora".
+    pub fn palette(name: &str) -> Option<Self> {
+        let (_, colors) = PALETTES.iter().find(|(palette, _)| *palette == name)?;
+        let mut config = NebulaConfig::default();
+        for (layer, color) in config.layers.iter_mut().zip(colors) {
+            layer.color = *color;
+        }
+        Some(config)
+    }
+
+    pub fn palette_names() -> impl Iterator<Item = &'static str> {
+        PALETTES.iter().map(|(name, _)| *name)
+    }
+}
+
+/// Noise-generated gas clouds, rendered once to an offscreen texture and
+/// slowly rotated and drifted behind the stars.
+pub struct Nebula {
+    texture: HtmlCanvasElement,
+    width: f64,
+    height: f64,
+    drift: f64,
+    time: f64,
+}
+
+impl Nebula {
+    pub fn new(config: &NebulaConfig, width: f64, height: f64) -> Self {
+        let mut rng = random::rng();
+        // Square texture covering the diagonal, so rotating it never exposes
+        // the corners of the canvas.
+        let diagonal = (width * width + height * height).sqrt();
+        let size = ((diagonal * config.resolution) as u32).max(1);
+
+        let layers: Vec<(Perlin, &NebulaLayer)> = config
+            .layers
+            .iter()
+            .map(|layer| (Perlin::new(&mut rng), layer))
+            .collect();
+
+        let mut data = vec![0u8; (size * size * 4) as usize];
+        for py in 0..size {
+            for px in 0..size {
+                let u = px as f64 / size as f64;
+                let v = py as f64 / size as f64;
+
+                // Fade out towards the texture edge so the clouds have no seams
+                let edge = ((u - 0.5).powi(2) + (v - 0.5).powi(2)).sqrt() * 2.0;
+                let vignette = (1.0 - edge).clamp(0.0, 1.0);
+
+                let (mut r, mut g, mut b, mut a) = (0.0, 0.0, 0.0, 0.0);
+                for (perlin, layer) in &layers {
+                    let n = perlin.warped(u * layer.scale, v * layer.scale, config.octaves, layer.warp);
+                    let density = smoothstep(layer.threshold, layer.threshold + 0.5, n) * layer.opacity * vignette;
+                    r += layer.color.0 as f64 * density;
+                    g += layer.color.1 as f64 * density;
+                    b += layer.color.2 as f64 * density;
+                    a += density;
+                }
+
+                let i = ((py * size + px) * 4) as usize;
+                if a > 0.0 {
+                    data[i] = (r / a).min(255.0) as u8;
+                    data[i + 1] = (g / a).min(255.0) as u8;
+                    data[i + 2] = (b / a).min(255.0) as u8;
+                    data[i + 3] = (a.min(1.0) * 255.0) as u8;
+                }
+            }
+        }
+
+        let texture = create_canvas(size, size);
+        let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&data), size, size).unwrap();
+        context_2d(&texture).put_image_data(&image, 0.0, 0.0).unwrap();
+
+        Nebula {
+            texture,
+            width,
+            height,
+            drift: config.drift,
+            time: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.time += dt;
+    }
+
+    pub fn draw(&self, context: &CanvasRenderingContext2d) {
+        let diagonal = (self.width * self.width + self.height * self.height).sqrt();
+        let angle = self.time * self.drift;
+        let offset_x = (self.time * self.drift * 0.7).sin() * self.width * 0.03;
+        let offset_y = (self.time * self.drift * 0.5).cos() * self.height * 0.03;
+        // Slow breathing so the gas doesn't look like a rotating photograph
+        let breath = 1.0 + (self.time * self.drift * 3.0).sin() * 0.04;
+
+        context.save();
+        context.translate(self.width / 2.0 + offset_x, self.height / 2.0 + offset_y).unwrap();
+        context.rotate(angle).unwrap();
+        context.scale(breath, breath).unwrap();
+        context
+            .draw_image_with_html_canvas_element_and_dw_and_dh(
+                &self.texture,
+                -diagonal / 2.0,
+                -diagonal / 2.0,
+                diagonal,
+                diagonal,
+            )
+            .unwrap();
+        context.restore();
+    }
+}
+
+fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
@@ -0,0 +1,85 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Classic 2D gradient (Perlin) noise with a shuffled permutation table.
+pub struct Perlin {
+    perm: [u8; 512],
+}
+
+impl Perlin {
+    pub fn new(rng: &mut impl Rng) -> Self {
+        let mut table: Vec<u8> = (0..=255).collect();
+        table.shuffle(rng);
+
+        let mut perm = [0u8; 512];
+        for (i, slot) in perm.iter_mut().enumerate() {
+            *slot = table[i & 255];
+        }
+        Perlin { perm }
+    }
+
+    /// Noise value in roughly `-1.0..1.0`.
+    pub fn noise(&self, x: f64, y: f64) -> f64 {
+        let xi = x.floor() as i64 as usize & 255;
+        let yi = y.floor() as i64 as usize & 255;
+        let xf = x - x.floor();
+        let yf = y - y.floor();
+
+        let u = fade(xf);
+        let v = fade(yf);
+
+        let aa = self.perm[self.perm[xi] as usize + yi];
+        let ab = self.perm[self.perm[xi] as usize + yi + 1];
+        let ba = self.perm[self.perm[xi + 1] as usize + yi];
+        let bb = self.perm[self.perm[xi + 1] as usize + yi + 1];
+
+        let x1 = lerp(grad(aa, xf, yf), grad(ba, xf - 1.0, yf), u);
+        let x2 = lerp(grad(ab, xf, yf - 1.0), grad(bb, xf - 1.0, yf - 1.0), u);
+        lerp(x1, x2, v)
+    }
+
+    /// Fractal Brownian motion: `octaves` layers of noise, each at double the
+    /// frequency and `gain` times the amplitude of the previous one.
+    pub fn fbm(&self, x: f64, y: f64, octaves: u32, gain: f64) -> f64 {
+        let mut sum = 0.0;
+        let mut amplitude = 0.5;
+        let mut frequency = 1.0;
+        let mut norm = 0.0;
+        for _ in 0..octaves {
+            sum += self.noise(x * frequency, y * frequency) * amplitude;
+            norm += amplitude;
+            amplitude *= gain;
+            frequency *= 2.0;
+        }
+        sum / norm
+    }
+
+    /// Domain-warped fBm (`f(p + k * q(p))`), which turns round blobs into the
+    /// stretched, filamentary shapes gas clouds have.
+    pub fn warped(&self, x: f64, y: f64, octaves: u32, warp: f64) -> f64 {
+        let qx = self.fbm(x, y, octaves, 0.5);
+        let qy = self.fbm(x + 5.2, y + 1.3, octaves, 0.5);
+        self.fbm(x + warp * qx, y + warp * qy, octaves, 0.5)
+    }
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+fn grad(hash: u8, x: f64, y: f64) -> f64 {
+    match hash & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
@@ -0,0 +1,191 @@
+//! Telescope and camera artifacts for bright light sources: diffraction
+//! spikes from the secondary mirror supports, chromatic halos and lens
+//! flare ghosts.
+
+use serde::{Deserialize, Serialize};
+use web_sys::CanvasRenderingContext2d;
+
+/// Diffraction spike layout, set by how the secondary mirror is held.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpikePattern {
+    /// No spikes, e.g. a refractor.
+    None,
+    /// Four spikes from a cross-shaped spider, as on Hubble.
+    Four,
+    Six,
+    Eight,
+    /// JWST: six spikes from the hexagonal mirror segments, plus two fainter
+    /// horizontal ones from the vertical strut.
+    Jwst,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpticsConfig {
+    pub spikes: SpikePattern,
+    pub spike_intensity: f64,
+    /// Spike length relative to the star's size.
+    pub spike_length: f64,
+    /// Rotation of the spike pattern in radians.
+    pub rotation: f64,
+    /// Colored ring from the lens' chromatic aberration.
+    pub halo_intensity: f64,
+    /// Ghost reflections for the brightest source; 0.0 disables them.
+    pub flare_intensity: f64,
+}
+
+impl Default for OpticsConfig {
+    fn default() -> Self {
+        OpticsConfig {
+            spikes: SpikePattern::Four,
+            spike_intensity: 1.0,
+            spike_length: 1.0,
+            rotation: 0.0,
+            halo_intensity: 0.5,
+            flare_intensity: 0.5,
+        }
+    }
+}
+
+impl OpticsConfig {
+    pub fn jwst() -> Self {
+        OpticsConfig {
+            spikes: SpikePattern::Jwst,
+            spike_length: 1.6,
+            rotation: std::f64::consts::PI / 2.0,
+            ..OpticsConfig::default()
+        }
+    }
+}
+
+// Ghost positions along the line through the screen center (0.0 is the
+// source, 1.0 its mirror image), radius relative to the source and tint
+const GHOSTS: [(f64, f64, (u8, u8, u8)); 5] = [
+    (0.45, 0.6, (120, 200, 255)),
+    (0.7, 0.25, (255, 220, 140)),
+    (1.0, 1.4, (140, 255, 200)),
+    (1.3, 0.4, (200, 140, 255)),
+    (1.8, 2.2, (120, 180, 255)),
+];
+
+pub struct Optics {
+    config: OpticsConfig,
+    center_x: f64,
+    center_y: f64,
+}
+
+impl Optics {
+    pub fn new(config: OpticsConfig, width: f64, height: f64) -> Self {
+        Optics {
+            config,
+            center_x: width / 2.0,
+            center_y: height / 2.0,
+        }
+    }
+
+    /// Spikes and halo around a star of `size` pixels; `brightness` (0.0..1.0)
+    /// scales how strongly they show.
+    pub fn draw_star(&self, context: &CanvasRenderingContext2d, x: f64, y: f64, size: f64, brightness: f64) {
+        let pi = std::f64::consts::PI;
+        let (strong, faint): (usize, Vec<f64>) = match self.config.spikes {
+            SpikePattern::None => (0, Vec::new()),
+            SpikePattern::Four => (4, Vec::new()),
+            SpikePattern::Six => (6, Vec::new()),
+            SpikePattern::Eight => (8, Vec::new()),
+            SpikePattern::Jwst => (6, vec![0.0, pi]),
+        };
+
+        context.set_global_composite_operation("lighter").unwrap();
+
+        let length = size * 6.0 * self.config.spike_length * (0.5 + brightness);
+        let alpha = brightness * self.config.spike_intensity * 0.6;
+        for k in 0..strong {
+            let angle = self.config.rotation + k as f64 * pi * 2.0 / strong as f64;
+            draw_spike(context, x, y, angle, length, size, alpha);
+        }
+        for angle in faint {
+            // The strut spikes sit at right angles to the pattern's axis
+            let angle = self.config.rotation + pi / 2.0 + angle;
+            draw_spike(context, x, y, angle, length * 0.6, size * 0.6, alpha * 0.5);
+        }
+
+        if self.config.halo_intensity > 0.0 {
+            self.draw_halo(context, x, y, size * 4.0, brightness * self.config.halo_intensity);
+        }
+
+        context.set_global_composite_operation("source-over").unwrap();
+    }
+
+    /// Faint ring, blue on the inside and red on the outside.
+    fn draw_halo(&self, context: &CanvasRenderingContext2d, x: f64, y: f64, radius: f64, alpha: f64) {
+        let gradient = context.create_radial_gradient(x, y, 0.0, x, y, radius).unwrap();
+        gradient.add_color_stop(0.0, "rgba(255, 255, 255, 0)").unwrap();
+        gradient.add_color_stop(0.55, "rgba(255, 255, 255, 0)").unwrap();
+        gradient.add_color_stop(0.7, &format!("rgba(110, 150, 255, {})", alpha * 0.25)).unwrap();
+        gradient.add_color_stop(0.8, &format!("rgba(140, 255, 170, {})", alpha * 0.2)).unwrap();
+        gradient.add_color_stop(0.9, &format!("rgba(255, 130, 110, {})", alpha * 0.2)).unwrap();
+        gradient.add_color_stop(1.0, "rgba(255, 130, 110, 0)").unwrap();
+        context.set_fill_style_canvas_gradient(&gradient);
+        context.begin_path();
+        context.arc(x, y, radius, 0.0, std::f64::consts::PI * 2.0).unwrap();
+        context.fill();
+    }
+
+    /// Lens flare for a bright source at `(x, y)`: ghosts strung along the
+    /// line through the screen center, plus a horizontal anamorphic streak.
+    pub fn draw_flare(&self, context: &CanvasRenderingContext2d, x: f64, y: f64, size: f64, brightness: f64) {
+        let intensity = brightness * self.config.flare_intensity;
+        if intensity <= 0.0 {
+            return;
+        }
+
+        context.set_global_composite_operation("lighter").unwrap();
+
+        let (dx, dy) = (self.center_x - x, self.center_y - y);
+        for &(position, radius, (r, g, b)) in &GHOSTS {
+            let (gx, gy) = (x + dx * 2.0 * position, y + dy * 2.0 * position);
+            let radius = size * 4.0 * radius;
+            let gradient = context.create_radial_gradient(gx, gy, 0.0, gx, gy, radius).unwrap();
+            gradient.add_color_stop(0.0, &format!("rgba({}, {}, {}, {})", r, g, b, intensity * 0.08)).unwrap();
+            gradient.add_color_stop(0.8, &format!("rgba({}, {}, {}, {})", r, g, b, intensity * 0.12)).unwrap();
+            gradient.add_color_stop(1.0, &format!("rgba({}, {}, {}, 0)", r, g, b)).unwrap();
+            context.set_fill_style_canvas_gradient(&gradient);
+            context.begin_path();
+            context.arc(gx, gy, radius, 0.0, std::f64::consts::PI * 2.0).unwrap();
+            context.fill();
+        }
+
+        let streak = size * 30.0 * (0.5 + brightness);
+        let gradient = context.create_linear_gradient(x - streak, y, x + streak, y);
+        gradient.add_color_stop(0.0, "rgba(120, 180, 255, 0)").unwrap();
+        gradient.add_color_stop(0.5, &format!("rgba(160, 210, 255, {})", intensity * 0.35)).unwrap();
+        gradient.add_color_stop(1.0, "rgba(120, 180, 255, 0)").unwrap();
+        context.set_fill_style_canvas_gradient(&gradient);
+        context.fill_rect(x - streak, y - size * 0.3, streak * 2.0, size * 0.6);
+
+        context.set_global_composite_operation("source-over").unwrap();
+    }
+}
+
+/// A tapered spike, drawn once per color channel with red reaching
+/// furthest, the way diffraction spreads longer wavelengths more.
+fn draw_spike(context: &CanvasRenderingContext2d, x: f64, y: f64, angle: f64, length: f64, width: f64, alpha: f64) {
+    let (dx, dy) = (angle.cos(), angle.sin());
+    let (nx, ny) = (-dy * width * 0.25, dx * width * 0.25);
+
+    for (reach, color) in [(1.0, "255, 90, 90"), (0.92, "90, 255, 90"), (0.84, "90, 90, 255")] {
+        let (tip_x, tip_y) = (x + dx * length * reach, y + dy * length * reach);
+        let gradient = context.create_linear_gradient(x, y, tip_x, tip_y);
+        gradient.add_color_stop(0.0, &format!("rgba({}, {})", color, alpha)).unwrap();
+        gradient.add_color_stop(1.0, &format!("rgba({}, 0)", color)).unwrap();
+        context.set_fill_style_canvas_gradient(&gradient);
+
+        context.begin_path();
+        context.move_to(x + nx, y + ny);
+        context.line_to(tip_x, tip_y);
+        context.line_to(x - nx, y - ny);
+        context.close_path();
+        context.fill();
+    }
+}
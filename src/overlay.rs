@@ -0,0 +1,171 @@
+//! Debug overlay drawn over the starfield, for profiling on real devices:
+//! frame rate and timings, star and draw call counts, renderer and seed.
+
+use crate::quality::QualityTier;
+use js_sys::{Function, Object, Reflect};
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+/// Renderer the frames are drawn with.
+pub const BACKEND: &str = "Canvas 2D";
+
+// Frames kept for the histogram, two seconds at 60 fps
+const SAMPLES: usize = 120;
+// Width of a histogram bucket and the frame time of the last one, which
+// also takes everything slower, in milliseconds
+const BUCKET: f64 = 2.0;
+const GRAPH_MAX: f64 = 50.0;
+
+// Wraps the context's drawing methods with a counter. Canvas 2D has no way to
+// count draw calls, and the methods are looked up on the object on every call,
+// so patching this one context instance catches everything drawn on it.
+// Bundled as a module rather than evaluated, so it works under a strict CSP.
+#[wasm_bindgen(inline_js = "
+    const NAMES = ['fill', 'stroke', 'fillRect', 'strokeRect', 'fillText', 'strokeText', 'drawImage', 'putImageData'];
+    export function instrument(context, counter) {
+        for (const name of NAMES) {
+            const original = context[name];
+            context[name] = function () {
+                counter.calls += 1;
+                return original.apply(this, arguments);
+            };
+        }
+        // Removing the wrappers uncovers the prototype's methods again
+        return () => NAMES.forEach((name) => delete context[name]);
+    }
+")]
+extern "C" {
+    fn instrument(context: &CanvasRenderingContext2d, counter: &Object) -> Function;
+}
+
+pub struct DebugOverlay {
+    // frame interval, time spent rendering, in milliseconds
+    frames: VecDeque<(f64, f64)>,
+    counter: Object,
+    // contexts being counted, and the functions that undo their patch
+    patched: Vec<(CanvasRenderingContext2d, Function)>,
+    draw_calls: u32,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        let counter = Object::new();
+        Reflect::set(&counter, &"calls".into(), &0.into()).unwrap();
+        DebugOverlay {
+            frames: VecDeque::with_capacity(SAMPLES),
+            counter,
+            patched: Vec::new(),
+            draw_calls: 0,
+        }
+    }
+
+    /// Counts draw calls made on `contexts` from now on. Each context is
+    /// patched once, and contexts left out since the last call are restored.
+    pub fn instrument(&mut self, contexts: &[&CanvasRenderingContext2d]) {
+        self.patched.retain(|(patched, restore)| {
+            let kept = contexts.contains(&patched);
+            if !kept {
+                restore.call0(&JsValue::NULL).unwrap();
+            }
+            kept
+        });
+        for &context in contexts {
+            if !self.patched.iter().any(|(patched, _)| patched == context) {
+                let restore = instrument(context, &self.counter);
+                self.patched.push((context.clone(), restore));
+            }
+        }
+    }
+
+    fn take_draw_calls(&self) -> u32 {
+        let calls = Reflect::get(&self.counter, &"calls".into()).unwrap().as_f64().unwrap_or(0.0);
+        Reflect::set(&self.counter, &"calls".into(), &0.into()).unwrap();
+        calls as u32
+    }
+
+    /// Records a finished frame, before the overlay itself is drawn.
+    pub fn record(&mut self, interval: f64, work: f64) {
+        if self.frames.len() == SAMPLES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back((interval, work));
+        self.draw_calls = self.take_draw_calls();
+    }
+
+    pub fn draw(&self, context: &CanvasRenderingContext2d, stars: usize, seed: u64, tier: Option<QualityTier>) {
+        let n = self.frames.len().max(1) as f64;
+        let interval = self.frames.iter().map(|frame| frame.0).sum::<f64>() / n;
+        let work = self.frames.iter().map(|frame| frame.1).sum::<f64>() / n;
+        let fps = if interval > 0.0 { 1000.0 / interval } else { 0.0 };
+
+        let (x, y, width) = (12.0, 12.0, 260.0);
+        let lines = [
+            format!("{:.1} fps · {:.1} ms · work {:.1} ms", fps, interval, work),
+            format!("{} stars · {} draw calls", stars, self.draw_calls),
+            format!("{} · seed {}", BACKEND, seed),
+            match tier {
+                Some(tier) => format!("quality {:?}", tier),
+                None => "quality fixed".to_string(),
+            },
+        ];
+        let graph_height = 40.0;
+        let height = 20.0 + lines.len() as f64 * 16.0 + graph_height + 10.0;
+
+        context.set_fill_style_str("rgba(10, 20, 20, 0.85)");
+        context.fill_rect(x, y, width, height);
+        context.set_stroke_style_str("rgba(0, 255, 200, 0.3)");
+        context.set_line_width(1.0);
+        context.stroke_rect(x, y, width, height);
+
+        context.set_font("12px monospace");
+        context.set_text_align("left");
+        context.set_text_baseline("alphabetic");
+        context.set_fill_style_str("rgba(255, 255, 255, 0.9)");
+        for (i, line) in lines.iter().enumerate() {
+            context.fill_text(line, x + 10.0, y + 22.0 + i as f64 * 16.0).unwrap();
+        }
+
+        // Frame time histogram, frame times left to right, colored by how
+        // many vsyncs the bucket misses
+        let buckets = (GRAPH_MAX / BUCKET) as usize;
+        let mut counts = vec![0u32; buckets];
+        for &(interval, _) in &self.frames {
+            counts[((interval / BUCKET) as usize).min(buckets - 1)] += 1;
+        }
+        let tallest = counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+        let (graph_x, graph_y, graph_width) = (x + 10.0, y + 20.0 + lines.len() as f64 * 16.0 + graph_height, width - 20.0);
+        let bucket_width = graph_width / buckets as f64;
+        for (i, &count) in counts.iter().enumerate() {
+            let interval = i as f64 * BUCKET;
+            let bar = count as f64 / tallest * graph_height;
+            let color = if interval < 1000.0 / 55.0 {
+                "rgba(0, 255, 200, 0.8)"
+            } else if interval < 1000.0 / 28.0 {
+                "rgba(255, 210, 80, 0.8)"
+            } else {
+                "rgba(255, 90, 90, 0.8)"
+            };
+            context.set_fill_style_str(color);
+            context.fill_rect(graph_x + i as f64 * bucket_width, graph_y - bar, bucket_width - 1.0, bar);
+        }
+
+        // 60 fps budget marker
+        let budget_x = graph_x + 1000.0 / 60.0 / GRAPH_MAX * graph_width;
+        context.set_stroke_style_str("rgba(255, 255, 255, 0.3)");
+        context.begin_path();
+        context.move_to(budget_x, graph_y - graph_height);
+        context.line_to(budget_x, graph_y);
+        context.stroke();
+
+        // Leave the overlay's own calls out of the next frame's count
+        self.take_draw_calls();
+    }
+}
+
+impl Drop for DebugOverlay {
+    // Hidden again, so stop counting
+    fn drop(&mut self) {
+        self.instrument(&[]);
+    }
+}
@@ -0,0 +1,249 @@
+//! Named, versioned bundles of starfield settings, stored as JSON or TOML.
+//!
+//! A preset mirrors the `Starfield` props it can set; anything it leaves out
+//! keeps its default. Every preset carries the format `version` it was
+//! written for, so older files can be migrated when the format changes.
+
+use crate::bloom::BloomConfig;
+use crate::effects::{
+    BlackHoleConfig, Constellation, GalaxyConfig, GravityConfig, MorphConfig, SkyConfig, TunnelConfig, WarpConfig,
+};
+use crate::nebula::NebulaConfig;
+use crate::optics::OpticsConfig;
+use crate::shape::DistributionConfig;
+use crate::starfield::StarEffect;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Version of the preset format written by this build.
+pub const PRESET_VERSION: u32 = 1;
+
+// name, TOML source
+const BUNDLED: [(&str, &str); 3] = [
+    ("calm", include_str!("../assets/presets/calm.toml")),
+    ("hyperdrive", include_str!("../assets/presets/hyperdrive.toml")),
+    ("deep field", include_str!("../assets/presets/deep_field.toml")),
+];
+
+thread_local! {
+    // The bundled sources, parsed on first use
+    static PARSED: Vec<(&'static str, Preset)> = BUNDLED
+        .iter()
+        .map(|(name, source)| (*name, Preset::from_toml(source).expect("bundled preset is malformed")))
+        .collect();
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preset {
+    pub version: u32,
+    pub effect: StarEffect,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warp: Option<WarpConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub morph: Option<MorphConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub galaxy: Option<GalaxyConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub black_hole: Option<BlackHoleConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tunnel: Option<TunnelConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gravity: Option<GravityConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sky: Option<SkyConfig>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub constellations: Vec<Constellation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distribution: Option<DistributionConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nebula: Option<NebulaConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trails: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bloom: Option<BloomConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optics: Option<OpticsConfig>,
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        Preset {
+            version: PRESET_VERSION,
+            effect: StarEffect::Warp,
+            seed: None,
+            warp: None,
+            morph: None,
+            galaxy: None,
+            black_hole: None,
+            tunnel: None,
+            gravity: None,
+            sky: None,
+            constellations: Vec::new(),
+            distribution: None,
+            nebula: None,
+            trails: None,
+            bloom: None,
+            optics: None,
+        }
+    }
+}
+
+/// Where `Starfield` gets a preset from.
+#[derive(Clone, PartialEq)]
+pub enum PresetSource {
+    /// A bundled preset, see `Preset::named`.
+    Named(String),
+    Inline(Box<Preset>),
+}
+
+impl From<&str> for PresetSource {
+    fn from(name: &str) -> Self {
+        PresetSource::Named(name.to_string())
+    }
+}
+
+impl From<Preset> for PresetSource {
+    fn from(preset: Preset) -> Self {
+        PresetSource::Inline(Box::new(preset))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PresetError {
+    /// The text isn't valid JSON/TOML or doesn't match the preset layout.
+    Parse(String),
+    /// Written by a newer build than this one.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PresetError::Parse(message) => write!(f, "invalid preset: {}", message),
+            PresetError::UnsupportedVersion(version) => write!(
+                f,
+                "preset version {} is newer than the supported version {}",
+                version, PRESET_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PresetError {}
+
+impl Preset {
+    /// One of the bundled presets: "calm", "hyperdrive" or "deep field".
+    /// Case, underscores and dashes are ignored.
+    pub fn named(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase().replace(['_', '-'], " ");
+        PARSED.with(|parsed| {
+            parsed
+                .iter()
+                .find(|(bundled, _)| *bundled == name)
+                .map(|(_, preset)| preset.clone())
+        })
+    }
+
+    /// Names of the bundled presets.
+    pub fn bundled_names() -> impl Iterator<Item = &'static str> {
+        BUNDLED.iter().map(|(name, _)| *name)
+    }
+
+    pub fn from_json(source: &str) -> Result<Self, PresetError> {
+        serde_json::from_str::<Preset>(source)
+            .map_err(|error| PresetError::Parse(error.to_string()))?
+            .checked()
+    }
+
+    pub fn from_toml(source: &str) -> Result<Self, PresetError> {
+        toml::from_str::<Preset>(source)
+            .map_err(|error| PresetError::Parse(error.to_string()))?
+            .checked()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    /// Rejects presets from the future. Older versions would be migrated here.
+    fn checked(self) -> Result<Self, PresetError> {
+        if self.version > PRESET_VERSION {
+            return Err(PresetError::UnsupportedVersion(self.version));
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Preset {
+        Preset {
+            effect: StarEffect::BlackHole,
+            seed: Some(42),
+            black_hole: Some(BlackHoleConfig::default()),
+            morph: Some(MorphConfig::text("Hello")),
+            trails: Some(0.9),
+            ..Preset::default()
+        }
+    }
+
+    #[test]
+    fn bundled_presets_parse() {
+        for name in Preset::bundled_names() {
+            let preset = Preset::named(name).unwrap();
+            assert_eq!(preset.version, PRESET_VERSION);
+        }
+        assert!(Preset::named("Deep-Field").is_some());
+        assert!(Preset::named("nonexistent").is_none());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let preset = sample();
+        assert!(Preset::from_json(&preset.to_json()) == Ok(preset));
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let preset = sample();
+        assert!(Preset::from_toml(&preset.to_toml()) == Ok(preset));
+    }
+
+    #[test]
+    fn missing_version_is_current() {
+        let preset = Preset::from_json(r#"{ "effect": "tunnel" }"#).unwrap();
+        assert_eq!(preset.version, PRESET_VERSION);
+        assert!(preset.effect == StarEffect::Tunnel);
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let source = format!(r#"{{ "version": {}, "effect": "warp" }}"#, PRESET_VERSION + 1);
+        assert!(Preset::from_json(&source) == Err(PresetError::UnsupportedVersion(PRESET_VERSION + 1)));
+    }
+
+    #[test]
+    fn partial_configs_fill_in_defaults() {
+        let preset = Preset::from_toml(
+            r#"
+                effect = "warp"
+                [morph]
+                target = { text = "Hi" }
+                [distribution]
+                image = "logo.png"
+            "#,
+        )
+        .unwrap();
+        assert!(preset.morph.unwrap().particles == MorphConfig::text("Hi").particles);
+        assert_eq!(preset.distribution.unwrap().stars, DistributionConfig::new("logo.png").stars);
+    }
+}
@@ -0,0 +1,229 @@
+//! Adapts rendering quality to the device by watching frame times. Frames
+//! are judged against the display's own refresh interval, read from the
+//! frame times themselves, so a 30 Hz or 120 Hz display works like 60 Hz.
+
+use std::collections::VecDeque;
+
+/// Quality steps, from cheapest to full detail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QualityTier {
+    Minimal,
+    Low,
+    Medium,
+    High,
+}
+
+impl QualityTier {
+    const ALL: [QualityTier; 4] = [QualityTier::Minimal, QualityTier::Low, QualityTier::Medium, QualityTier::High];
+
+    fn step(self, by: i32) -> Self {
+        let index = (self as i32 + by).clamp(0, QualityTier::ALL.len() as i32 - 1);
+        QualityTier::ALL[index as usize]
+    }
+
+    /// What effects should draw at this tier.
+    pub fn detail(self) -> Detail {
+        match self {
+            QualityTier::High => Detail { stars: 1.0, glow: true },
+            QualityTier::Medium => Detail { stars: 0.7, glow: true },
+            QualityTier::Low => Detail { stars: 0.5, glow: false },
+            QualityTier::Minimal => Detail { stars: 0.35, glow: false },
+        }
+    }
+
+    /// Whether trails and bloom run.
+    pub fn post_processing(self) -> bool {
+        self == QualityTier::High
+    }
+
+    /// Canvas backing store resolution relative to its CSS size.
+    pub fn resolution(self) -> f64 {
+        match self {
+            QualityTier::High | QualityTier::Medium => 1.0,
+            QualityTier::Low => 0.75,
+            QualityTier::Minimal => 0.5,
+        }
+    }
+}
+
+/// Per-effect level of detail.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Detail {
+    /// Share of the stars that are drawn (0.0..1.0).
+    pub stars: f64,
+    /// Extra glow layers, halos and spikes.
+    pub glow: bool,
+}
+
+impl Default for Detail {
+    fn default() -> Self {
+        QualityTier::High.detail()
+    }
+}
+
+impl Detail {
+    /// How many of `total` stars to draw.
+    pub fn count(&self, total: usize) -> usize {
+        (total as f64 * self.stars.clamp(0.0, 1.0)).ceil() as usize
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct QualityConfig {
+    /// Number of most recent frames averaged for each decision.
+    pub window: usize,
+    /// Share the average frame interval may run over the refresh interval
+    /// before quality drops, so vsync jitter doesn't count.
+    pub slack: f64,
+    /// Share of the refresh interval the frame work must stay under before
+    /// quality goes back up.
+    pub headroom: f64,
+    /// Consecutive frames with headroom needed to step up, so a tier that
+    /// just stepped down doesn't bounce straight back.
+    pub patience: u32,
+    /// Frames to hold a new tier before deciding again, long enough for
+    /// the window to fill with frames drawn at it.
+    pub cooldown: u32,
+    pub initial: QualityTier,
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        QualityConfig {
+            window: 90,
+            slack: 0.1,
+            headroom: 0.5,
+            patience: 270,
+            cooldown: 90,
+            initial: QualityTier::High,
+        }
+    }
+}
+
+pub struct QualityGovernor {
+    config: QualityConfig,
+    tier: QualityTier,
+    // interval between frames, time spent rendering the frame
+    samples: VecDeque<(f64, f64)>,
+    good_frames: u32,
+    cooldown: u32,
+}
+
+impl QualityGovernor {
+    pub fn new(config: QualityConfig) -> Self {
+        QualityGovernor {
+            tier: config.initial,
+            samples: VecDeque::with_capacity(config.window + 1),
+            good_frames: 0,
+            cooldown: 0,
+            config,
+        }
+    }
+
+    pub fn tier(&self) -> QualityTier {
+        self.tier
+    }
+
+    /// Records one frame and returns the new tier if it changed. Both the
+    /// interval and the work are needed: the interval is capped by the
+    /// display's refresh rate, so only the work shows how much headroom is left.
+    pub fn record(&mut self, interval: f64, work: f64) -> Option<QualityTier> {
+        let window = self.config.window.max(1);
+        self.samples.push_back((interval, work));
+        if self.samples.len() > window {
+            self.samples.pop_front();
+        }
+        if self.cooldown > 0 {
+            self.cooldown -= 1;
+            return None;
+        }
+        if self.samples.len() < window {
+            return None;
+        }
+
+        let n = self.samples.len() as f64;
+        let interval = self.samples.iter().map(|sample| sample.0).sum::<f64>() / n;
+        let work = self.samples.iter().map(|sample| sample.1).sum::<f64>() / n;
+        // Most frames land on vsync, so the median interval is the display's
+        let mut intervals: Vec<f64> = self.samples.iter().map(|sample| sample.0).collect();
+        intervals.sort_by(f64::total_cmp);
+        let refresh = intervals[intervals.len() / 2];
+
+        let previous = self.tier;
+        // Dropped frames, or work that can't fit in a refresh at all
+        if interval > refresh * (1.0 + self.config.slack) || work > refresh {
+            self.good_frames = 0;
+            self.tier = self.tier.step(-1);
+        } else if work < refresh * self.config.headroom {
+            self.good_frames += 1;
+            if self.good_frames >= self.config.patience {
+                self.good_frames = 0;
+                self.tier = self.tier.step(1);
+            }
+        } else {
+            self.good_frames = 0;
+        }
+
+        if self.tier == previous {
+            return None;
+        }
+        self.cooldown = self.config.cooldown;
+        Some(self.tier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: f64 = 1000.0 / 60.0;
+
+    fn governor(initial: QualityTier) -> QualityGovernor {
+        QualityGovernor::new(QualityConfig { window: 10, patience: 5, cooldown: 10, initial, ..QualityConfig::default() })
+    }
+
+    // Feeds frames until the tier changes, returning it and the frame it changed on
+    fn run(governor: &mut QualityGovernor, frames: usize, frame: impl Fn(usize) -> (f64, f64)) -> Option<(usize, QualityTier)> {
+        (0..frames).find_map(|i| {
+            let (interval, work) = frame(i);
+            governor.record(interval, work).map(|tier| (i, tier))
+        })
+    }
+
+    #[test]
+    fn dropped_frames_step_down_once_the_window_fills() {
+        let mut governor = governor(QualityTier::High);
+        // Every third frame misses vsync
+        let dropping = |i: usize| if i % 3 == 2 { (FRAME * 2.0, FRAME * 1.2) } else { (FRAME, FRAME * 0.9) };
+        assert_eq!(run(&mut governor, 100, dropping), Some((9, QualityTier::Medium)));
+    }
+
+    #[test]
+    fn cooldown_holds_a_new_tier() {
+        let mut governor = governor(QualityTier::High);
+        let dropping = |i: usize| if i % 3 == 2 { (FRAME * 2.0, FRAME * 1.2) } else { (FRAME, FRAME * 0.9) };
+        run(&mut governor, 100, dropping);
+        // Still dropping, but nothing is decided until the cooldown passes
+        assert_eq!(run(&mut governor, 100, dropping), Some((10, QualityTier::Low)));
+    }
+
+    #[test]
+    fn headroom_steps_up_after_patience() {
+        let mut governor = governor(QualityTier::Low);
+        let idle = |_| (FRAME, 2.0);
+        // The window fills on the 10th frame, then 5 good frames in a row
+        assert_eq!(run(&mut governor, 100, idle), Some((13, QualityTier::Medium)));
+        assert_eq!(run(&mut governor, 100, idle), Some((14, QualityTier::High)));
+        assert_eq!(run(&mut governor, 100, idle), None);
+    }
+
+    #[test]
+    fn steady_slow_displays_keep_their_tier() {
+        for hz in [30.0, 60.0, 120.0, 144.0] {
+            let mut governor = governor(QualityTier::Medium);
+            let refresh = 1000.0 / hz;
+            // Work fits the refresh, without enough headroom to step up
+            assert_eq!(run(&mut governor, 200, |_| (refresh, refresh * 0.7)), None, "{hz} Hz");
+        }
+    }
+}
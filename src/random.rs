@@ -0,0 +1,25 @@
+//! Seedable randomness. Effects and layers draw their generators from one
+//! seeded stream, so a scene can be reproduced from its seed.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+
+thread_local! {
+    static STREAM: RefCell<StdRng> = RefCell::new(StdRng::seed_from_u64(0));
+}
+
+/// Restarts the stream from `seed`.
+pub fn reseed(seed: u64) {
+    STREAM.with(|stream| *stream.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// A short seed for when none is configured; short so it's easy to note down.
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen_range(0..1_000_000)
+}
+
+/// A generator forked off the stream.
+pub fn rng() -> StdRng {
+    StdRng::seed_from_u64(STREAM.with(|stream| stream.borrow_mut().gen()))
+}
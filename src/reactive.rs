@@ -0,0 +1,291 @@
+//! Music-driven starfields: an `AnalyserNode` splits the audio into
+//! frequency bands and each band drives a `Starfield` setting. By default
+//! the bass pushes the warp speed, the mids brighten the twinkle and the
+//! highs fill in the stars.
+//!
+//! ```text
+//! let reactive = AudioReactive::from_file(&file, ReactiveConfig::default());
+//! html! { <Starfield effect={StarEffect::Twinkle} reactive={reactive} /> }
+//! ```
+
+use crate::timeline::Param;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{AnalyserNode, AudioContext, File, HtmlInputElement, HtmlMediaElement, Url};
+use yew::prelude::*;
+
+/// A frequency range and the setting it drives.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BandMapping {
+    /// Range in Hz.
+    pub low: f64,
+    pub high: f64,
+    pub param: Param,
+    /// Setting at silence and at full level.
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReactiveConfig {
+    /// When two bands drive the same setting, the later one wins.
+    pub bands: Vec<BandMapping>,
+    /// Multiplies the band levels; raise it for quiet recordings.
+    pub gain: f64,
+    /// Seconds for a setting to follow the audio as it gets louder.
+    pub attack: f64,
+    /// Seconds for a setting to settle back as it gets quieter; longer than
+    /// the attack so beats punch in and fade out.
+    pub release: f64,
+}
+
+impl Default for ReactiveConfig {
+    fn default() -> Self {
+        ReactiveConfig {
+            bands: vec![
+                BandMapping {
+                    low: 20.0,
+                    high: 250.0,
+                    param: Param::Speed,
+                    min: 0.5,
+                    max: 4.0,
+                },
+                BandMapping {
+                    low: 250.0,
+                    high: 2000.0,
+                    param: Param::Brightness,
+                    min: 0.6,
+                    max: 1.8,
+                },
+                BandMapping {
+                    low: 2000.0,
+                    high: 12000.0,
+                    param: Param::Density,
+                    min: 0.3,
+                    max: 1.0,
+                },
+            ],
+            gain: 1.0,
+            attack: 0.05,
+            release: 0.4,
+        }
+    }
+}
+
+struct ReactiveState {
+    config: ReactiveConfig,
+    context: AudioContext,
+    element: HtmlMediaElement,
+    // Wakes the context when the element plays, until stopped
+    resume: Closure<dyn FnMut()>,
+    analyser: AnalyserNode,
+    // Magnitudes of the last analysis, and the smoothed level of each band
+    spectrum: Vec<u8>,
+    levels: Vec<f64>,
+}
+
+/// Audio analysed for a `Starfield`: pass it as the `reactive` prop. Playback
+/// stays under the media element's control.
+#[derive(Clone)]
+pub struct AudioReactive(Rc<RefCell<ReactiveState>>);
+
+impl PartialEq for AudioReactive {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl AudioReactive {
+    /// Listens to an `<audio>` or `<video>` element on the page, which keeps
+    /// playing through the speakers. An element can only be routed through
+    /// Web Audio once, so create one of these per element. `None` where Web
+    /// Audio isn't available or the element is already routed.
+    pub fn from_element(element: &HtmlMediaElement, config: ReactiveConfig) -> Option<Self> {
+        let context = AudioContext::new().ok()?;
+        let Ok(source) = context.create_media_element_source(element) else {
+            let _ = context.close();
+            return None;
+        };
+        let analyser = context.create_analyser().unwrap();
+        analyser.set_fft_size(2048);
+        // The analyser's own smoothing takes the jitter out between frames;
+        // attack and release shape the rest
+        analyser.set_smoothing_time_constant(0.5);
+        source.connect_with_audio_node(&analyser).unwrap();
+        analyser.connect_with_audio_node(&context.destination()).unwrap();
+
+        // A context created before any input starts suspended, so wake it
+        // when the element starts playing
+        let resume = {
+            let context = context.clone();
+            Closure::<dyn FnMut()>::new(move || {
+                let _ = context.resume();
+            })
+        };
+        element.add_event_listener_with_callback("play", resume.as_ref().unchecked_ref()).unwrap();
+
+        let levels = vec![0.0; config.bands.len()];
+        Some(AudioReactive(Rc::new(RefCell::new(ReactiveState {
+            spectrum: vec![0; analyser.frequency_bin_count() as usize],
+            config,
+            context,
+            element: element.clone(),
+            resume,
+            analyser,
+            levels,
+        }))))
+    }
+
+    /// Plays a local file, e.g. one picked with `<input type="file">`, on a
+    /// loop. `None` where Web Audio isn't available.
+    pub fn from_file(file: &File, config: ReactiveConfig) -> Option<Self> {
+        let element = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .create_element("audio")
+            .unwrap()
+            .dyn_into::<HtmlMediaElement>()
+            .unwrap();
+        let url = Url::create_object_url_with_blob(file).ok()?;
+        element.set_src(&url);
+        element.set_loop(true);
+        let Some(reactive) = AudioReactive::from_element(&element, config) else {
+            Url::revoke_object_url(&url).unwrap();
+            return None;
+        };
+        let _ = element.play();
+        Some(reactive)
+    }
+
+    /// The element being analysed, for pausing or seeking.
+    pub fn element(&self) -> HtmlMediaElement {
+        self.0.borrow().element.clone()
+    }
+
+    /// Stops playback and releases the audio device.
+    pub fn stop(&self) {
+        let state = self.0.borrow();
+        let _ = state.element.pause();
+        state
+            .element
+            .remove_event_listener_with_callback("play", state.resume.as_ref().unchecked_ref())
+            .unwrap();
+        let _ = state.context.close();
+        let src = state.element.src();
+        if src.starts_with("blob:") {
+            Url::revoke_object_url(&src).unwrap();
+        }
+    }
+
+    /// Reads the current spectrum and returns the value each band gives its
+    /// setting. `dt` is the frame time in 60 Hz frames.
+    pub(crate) fn values(&self, dt: f64) -> Vec<(Param, f64)> {
+        let state = &mut *self.0.borrow_mut();
+        state.analyser.get_byte_frequency_data(&mut state.spectrum);
+        let hz_per_bin = state.context.sample_rate() as f64 / state.analyser.fft_size() as f64;
+        let seconds = dt / 60.0;
+
+        state
+            .config
+            .bands
+            .iter()
+            .zip(state.levels.iter_mut())
+            .map(|(band, level)| {
+                let bins = state.spectrum.len();
+                let first = ((band.low / hz_per_bin) as usize).min(bins);
+                let last = ((band.high / hz_per_bin).ceil() as usize).clamp(first, bins);
+                let bins = &state.spectrum[first..last];
+                let loudness = bins.iter().map(|&bin| bin as f64).sum::<f64>() / bins.len().max(1) as f64 / 255.0;
+                let target = (loudness * state.config.gain).clamp(0.0, 1.0);
+
+                // Follows the level with separate rise and fall times
+                let time = if target > *level { state.config.attack } else { state.config.release };
+                *level += (target - *level) * (1.0 - (-seconds / time.max(1e-3)).exp());
+                (band.param, band.min + (band.max - band.min) * *level)
+            })
+            .collect()
+    }
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct MusicPickerProps {
+    /// The music playing now, if any.
+    #[prop_or_default]
+    pub reactive: Option<AudioReactive>,
+    /// Called with the newly picked music, or `None` once it's stopped.
+    pub on_change: Callback<Option<AudioReactive>>,
+}
+
+/// A button that picks a local audio file for the stars to follow, and
+/// stops it again.
+#[function_component(MusicPicker)]
+pub fn music_picker(props: &MusicPickerProps) -> Html {
+    let on_pick = {
+        let (reactive, on_change) = (props.reactive.clone(), props.on_change.clone());
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Some(file) = input.files().and_then(|files| files.get(0)) {
+                if let Some(previous) = reactive.as_ref() {
+                    previous.stop();
+                }
+                on_change.emit(AudioReactive::from_file(&file, ReactiveConfig::default()));
+            }
+            // Lets the same file be picked again after stopping
+            input.set_value("");
+        })
+    };
+    let on_stop = {
+        let (reactive, on_change) = (props.reactive.clone(), props.on_change.clone());
+        Callback::from(move |_: MouseEvent| {
+            if let Some(previous) = reactive.as_ref() {
+                previous.stop();
+            }
+            on_change.emit(None);
+        })
+    };
+
+    html! {
+        <>
+            <style>
+                {r#"
+                    .music-picker {
+                        position: fixed;
+                        left: 1.25rem;
+                        bottom: 1.25rem;
+                        z-index: 5;
+                        padding: 0.6rem 1rem;
+                        border: 1px solid rgba(0, 255, 200, 0.3);
+                        border-radius: 10px;
+                        background: rgba(0, 20, 20, 0.6);
+                        color: white;
+                        font-size: 0.9rem;
+                        cursor: pointer;
+                    }
+                    .music-picker input {
+                        position: absolute;
+                        width: 1px;
+                        height: 1px;
+                        opacity: 0;
+                    }
+                    .music-picker:focus-visible, .music-picker:focus-within {
+                        outline: none;
+                        box-shadow: 0 0 0 3px rgba(0, 255, 200, 0.5);
+                    }
+                "#}
+            </style>
+            if props.reactive.is_some() {
+                <button type="button" class="music-picker" onclick={on_stop}>
+                    {"♫ Stop music"}
+                </button>
+            } else {
+                <label class="music-picker">
+                    {"♫ Play music"}
+                    <input type="file" accept="audio/*" onchange={on_pick} />
+                </label>
+            }
+        </>
+    }
+}
@@ -0,0 +1,201 @@
+//! Fullscreen screensaver for unattended displays: cycles through the
+//! effects with a fade between them, hides the cursor while it's idle and
+//! exits on a key press, click or tap.
+
+use crate::nebula::NebulaConfig;
+use crate::quality::QualityConfig;
+use crate::soundtrack::Soundtrack;
+use crate::starfield::{StarEffect, Starfield};
+use crate::timeline::{Param, Timeline, TimelineEvent};
+use crate::tween::Transition;
+use std::cell::Cell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event, EventTarget};
+use yew::prelude::*;
+
+// target, event type, listener
+type Listener = (EventTarget, &'static str, Closure<dyn FnMut(Event)>);
+
+fn all_effects() -> Vec<StarEffect> {
+    StarEffect::ALL.to_vec()
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ScreensaverProps {
+    /// Effects to cycle through, in order.
+    #[prop_or_else(all_effects)]
+    pub effects: Vec<StarEffect>,
+    /// Seconds each effect is shown.
+    #[prop_or(30.0)]
+    pub interval: f64,
+    /// How the stars fade out before each change and back in after it.
+    #[prop_or_default]
+    pub transition: Transition,
+    /// Seconds without pointer movement before the cursor is hidden.
+    #[prop_or(3.0)]
+    pub idle: f64,
+    /// Keeps playing through the screensaver if it was on.
+    #[prop_or_default]
+    pub soundtrack: Option<Soundtrack>,
+    /// Called when input ends the screensaver.
+    #[prop_or_default]
+    pub on_exit: Callback<()>,
+}
+
+/// A looping timeline showing each effect for `interval` seconds, thinning
+/// the stars out and fading the frame to its background around each cut.
+fn cycle(effects: &[StarEffect], interval: f64, transition: Transition) -> Timeline {
+    let interval = interval.max(1.0);
+    let fade = transition.duration.clamp(0.0, interval / 2.0);
+    let mut timeline = Timeline::new(interval * effects.len().max(1) as f64).looped();
+    for (i, effect) in effects.iter().enumerate() {
+        let start = i as f64 * interval;
+        let end = start + interval;
+        timeline = timeline
+            .event(start, TimelineEvent::Cut(*effect))
+            .event(start + interval / 2.0, TimelineEvent::ShootingStars(5))
+            .keyframe(Param::Density, start, 0.0, transition.easing)
+            .keyframe(Param::Density, start + fade, 1.0, transition.easing)
+            .keyframe(Param::Density, end - fade, 1.0, transition.easing)
+            .keyframe(Param::Density, end, 0.0, transition.easing)
+            .keyframe(Param::Opacity, start, 0.0, transition.easing)
+            .keyframe(Param::Opacity, start + fade, 1.0, transition.easing)
+            .keyframe(Param::Opacity, end - fade, 1.0, transition.easing)
+            .keyframe(Param::Opacity, end, 0.0, transition.easing);
+    }
+    timeline
+}
+
+#[function_component(Screensaver)]
+pub fn screensaver(props: &ScreensaverProps) -> Html {
+    let root = use_node_ref();
+    let timeline = use_memo((props.effects.clone(), props.interval, props.transition), |(effects, interval, transition)| {
+        cycle(effects, *interval, *transition)
+    });
+    let cursor_hidden = use_state_eq(|| false);
+    // The starfield sizes itself on mount, so it's remounted when going
+    // fullscreen changes the window size
+    let generation = use_state(|| 0u32);
+
+    {
+        let root = root.clone();
+        let cursor_hidden = cursor_hidden.clone();
+        let generation = generation.clone();
+        let (on_exit, idle) = (props.on_exit.clone(), props.idle);
+        use_effect_with((), move |_| {
+            let window = web_sys::window().unwrap();
+            let document = window.document().unwrap();
+            let element = root.cast::<Element>().unwrap();
+            // Browsers only allow this from a user gesture, so it may be
+            // refused here; the first input then tries again
+            let _ = element.request_fullscreen();
+
+            let was_fullscreen = Rc::new(Cell::new(false));
+            let retried = Rc::new(Cell::new(false));
+            let exit = {
+                let document = document.clone();
+                move || {
+                    if document.fullscreen_element().is_some() {
+                        document.exit_fullscreen();
+                    }
+                    on_exit.emit(());
+                }
+            };
+
+            let on_input = {
+                let (document, exit) = (document.clone(), exit.clone());
+                let (was_fullscreen, retried) = (was_fullscreen.clone(), retried.clone());
+                move |_: Event| {
+                    let refused = document.fullscreen_enabled() && document.fullscreen_element().is_none() && !was_fullscreen.get();
+                    if refused && !retried.get() {
+                        retried.set(true);
+                        let _ = element.request_fullscreen();
+                    } else {
+                        exit();
+                    }
+                }
+            };
+
+            let last_move = Rc::new(Cell::new(js_sys::Date::now()));
+            let on_move = {
+                let (last_move, cursor_hidden) = (last_move.clone(), cursor_hidden.clone());
+                move |_: Event| {
+                    last_move.set(js_sys::Date::now());
+                    cursor_hidden.set(false);
+                }
+            };
+
+            let on_fullscreen_change = {
+                let document = document.clone();
+                move |_: Event| {
+                    if document.fullscreen_element().is_some() {
+                        was_fullscreen.set(true);
+                    } else if was_fullscreen.get() {
+                        // Left with Escape or the browser's own controls
+                        exit();
+                        return;
+                    }
+                    generation.set(*generation + 1);
+                }
+            };
+
+            let window_target: EventTarget = window.clone().into();
+            let document_target: EventTarget = document.into();
+            let listeners: Vec<Listener> = vec![
+                (window_target.clone(), "keydown", Closure::new(on_input.clone())),
+                (window_target.clone(), "pointerdown", Closure::new(on_input)),
+                (window_target, "pointermove", Closure::new(on_move)),
+                (document_target, "fullscreenchange", Closure::new(on_fullscreen_change)),
+            ];
+            for (target, event, listener) in &listeners {
+                target.add_event_listener_with_callback(event, listener.as_ref().unchecked_ref()).unwrap();
+            }
+
+            let check_idle = Closure::<dyn FnMut()>::new(move || {
+                if js_sys::Date::now() - last_move.get() > idle * 1000.0 {
+                    cursor_hidden.set(true);
+                }
+            });
+            let timer = window
+                .set_interval_with_callback_and_timeout_and_arguments_0(check_idle.as_ref().unchecked_ref(), 500)
+                .unwrap();
+
+            move || {
+                for (target, event, listener) in &listeners {
+                    target.remove_event_listener_with_callback(event, listener.as_ref().unchecked_ref()).unwrap();
+                }
+                window.clear_interval_with_handle(timer);
+                drop(check_idle);
+            }
+        });
+    }
+
+    let first = props.effects.first().copied().unwrap_or(StarEffect::Warp);
+    html! {
+        <div ref={root} class={classes!("screensaver", cursor_hidden.then_some("idle"))}>
+            <style>
+                {r#"
+                    .screensaver {
+                        position: fixed;
+                        inset: 0;
+                        z-index: 10;
+                        background: #000;
+                    }
+                    .screensaver.idle {
+                        cursor: none;
+                    }
+                "#}
+            </style>
+            <Starfield
+                key={*generation}
+                effect={first}
+                timeline={(*timeline).clone()}
+                nebula={NebulaConfig::default()}
+                quality={QualityConfig::default()}
+                soundtrack={props.soundtrack.clone()}
+            />
+        </div>
+    }
+}
@@ -0,0 +1,180 @@
+//! Turns text and images into point distributions by rendering them to an
+//! offscreen canvas and sampling pixels weighted by luminance.
+
+use crate::canvas::{context_2d, create_canvas};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{CanvasRenderingContext2d, HtmlImageElement};
+
+/// Per-pixel luminance of a rendered shape, stored as a cumulative
+/// distribution so sampling a point is a binary search.
+pub struct LuminanceMap {
+    width: u32,
+    height: u32,
+    /// Scale from map pixels back to canvas pixels.
+    scale: f64,
+    cumulative: Vec<f64>,
+}
+
+impl LuminanceMap {
+    fn from_context(context: &CanvasRenderingContext2d, width: u32, height: u32, scale: f64) -> Self {
+        let data = context.get_image_data(0.0, 0.0, width as f64, height as f64).unwrap().data();
+
+        let mut cumulative = Vec::with_capacity((width * height) as usize);
+        let mut total = 0.0;
+        for pixel in data.chunks_exact(4) {
+            // Rec. 709 luma, weighted by alpha so transparent areas stay empty
+            let l = (0.2126 * pixel[0] as f64 + 0.7152 * pixel[1] as f64 + 0.0722 * pixel[2] as f64) / 255.0
+                * (pixel[3] as f64 / 255.0);
+            total += l;
+            cumulative.push(total);
+        }
+
+        LuminanceMap {
+            width,
+            height,
+            scale,
+            cumulative,
+        }
+    }
+
+    /// Renders `text` centered on a `width` x `height` canvas, as large as fits.
+    pub fn from_text(text: &str, width: f64, height: f64) -> Self {
+        // Half resolution is plenty for picking target points
+        let scale = 2.0;
+        let (w, h) = ((width / scale).max(1.0) as u32, (height / scale).max(1.0) as u32);
+        let canvas = create_canvas(w, h);
+        let context = context_2d(&canvas);
+
+        let font = "-apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif";
+        context.set_font(&format!("700 100px {}", font));
+        let measured = context.measure_text(text).unwrap().width().max(1.0);
+        let size = (100.0 * w as f64 * 0.85 / measured).min(h as f64 * 0.3);
+
+        context.set_font(&format!("700 {}px {}", size, font));
+        context.set_text_align("center");
+        context.set_text_baseline("middle");
+        context.set_fill_style_str("#ffffff");
+        context.fill_text(text, w as f64 / 2.0, h as f64 / 2.0).unwrap();
+
+        LuminanceMap::from_context(&context, w, h, scale)
+    }
+
+    /// Draws `image` scaled to fit (`cover == false`) or fill (`cover == true`)
+    /// a `width` x `height` canvas, centered.
+    pub fn from_image(image: &HtmlImageElement, width: f64, height: f64, cover: bool) -> Self {
+        let scale = 2.0;
+        let (w, h) = ((width / scale).max(1.0) as u32, (height / scale).max(1.0) as u32);
+        let canvas = create_canvas(w, h);
+        let context = context_2d(&canvas);
+
+        let (iw, ih) = (image.natural_width().max(1) as f64, image.natural_height().max(1) as f64);
+        let fit = if cover {
+            (w as f64 / iw).max(h as f64 / ih)
+        } else {
+            (w as f64 / iw).min(h as f64 / ih) * 0.8
+        };
+        let (dw, dh) = (iw * fit, ih * fit);
+        context
+            .draw_image_with_html_image_element_and_dw_and_dh(image, (w as f64 - dw) / 2.0, (h as f64 - dh) / 2.0, dw, dh)
+            .unwrap();
+
+        LuminanceMap::from_context(&context, w, h, scale)
+    }
+
+    /// A random point in canvas pixels, more likely where the shape is bright.
+    pub fn sample(&self, rng: &mut impl Rng) -> Option<(f64, f64)> {
+        let total = *self.cumulative.last()?;
+        if total <= 0.0 {
+            return None;
+        }
+        let target = rng.gen_range(0.0..total);
+        let i = self.cumulative.partition_point(|&c| c <= target).min(self.cumulative.len() - 1);
+
+        // Jitter within the pixel so samples don't line up on the grid
+        let x = (i as u32 % self.width) as f64 + rng.gen_range(0.0..1.0);
+        let y = (i as u32 / self.width) as f64 + rng.gen_range(0.0..1.0);
+        Some((x * self.scale, y * self.scale))
+    }
+
+    /// Luminance (0.0..1.0) at a canvas pixel position, 0.0 outside the map.
+    pub fn luminance(&self, x: f64, y: f64) -> f64 {
+        let (mx, my) = ((x / self.scale).floor(), (y / self.scale).floor());
+        if mx < 0.0 || my < 0.0 || mx >= self.width as f64 || my >= self.height as f64 {
+            return 0.0;
+        }
+        let i = my as usize * self.width as usize + mx as usize;
+        self.cumulative[i] - if i > 0 { self.cumulative[i - 1] } else { 0.0 }
+    }
+}
+
+/// Star layout that follows an image, so the field subtly reveals it.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DistributionConfig {
+    /// URL of the image to follow.
+    pub image: String,
+    /// Share of stars placed by the image (0.0..1.0); the rest are uniform.
+    pub strength: f64,
+    /// Star count; an image needs far more stars than a plain field to show.
+    pub stars: usize,
+}
+
+impl DistributionConfig {
+    pub fn new(image: &str) -> Self {
+        DistributionConfig {
+            image: image.to_string(),
+            strength: 0.75,
+            stars: 1200,
+        }
+    }
+}
+
+impl Default for DistributionConfig {
+    // No image, so every star is placed uniformly until one is given
+    fn default() -> Self {
+        DistributionConfig::new("")
+    }
+}
+
+/// A `DistributionConfig` whose image is loading in the background.
+pub struct ImageDistribution {
+    pub config: DistributionConfig,
+    map: Rc<RefCell<Option<LuminanceMap>>>,
+}
+
+impl ImageDistribution {
+    pub fn load(config: DistributionConfig, width: f64, height: f64) -> Self {
+        let map = Rc::new(RefCell::new(None));
+        {
+            let map = map.clone();
+            load_image(&config.image, move |image| {
+                *map.borrow_mut() = Some(LuminanceMap::from_image(image, width, height, true));
+            });
+        }
+        ImageDistribution { config, map }
+    }
+
+    /// The luminance map, handed out once after the image has loaded.
+    pub fn take_map(&self) -> Option<LuminanceMap> {
+        self.map.borrow_mut().take()
+    }
+}
+
+/// Loads an image (URL, data URL or object URL from an upload) and calls
+/// `on_load` once it has decoded. The image is requested with CORS so its
+/// pixels can be read back.
+pub fn load_image(url: &str, on_load: impl FnOnce(&HtmlImageElement) + 'static) {
+    let image = HtmlImageElement::new().unwrap();
+    image.set_cross_origin(Some("anonymous"));
+
+    let loaded = image.clone();
+    let closure = Closure::once(move || on_load(&loaded));
+    image.set_onload(Some(closure.as_ref().unchecked_ref()));
+    closure.forget();
+
+    image.set_src(url);
+}
@@ -0,0 +1,79 @@
+use crate::random;
+use rand::rngs::StdRng;
+use rand::Rng;
+use web_sys::CanvasRenderingContext2d;
+
+/// Meteors streaking across the frame on top of any effect, launched in
+/// bursts by timeline events.
+pub struct ShootingStars {
+    rng: StdRng,
+    width: f64,
+    height: f64,
+    // x, y, vx, vy, age, lifetime in frames
+    meteors: Vec<(f64, f64, f64, f64, f64, f64)>,
+}
+
+impl ShootingStars {
+    pub fn new(width: f64, height: f64) -> Self {
+        ShootingStars {
+            rng: random::rng(),
+            width,
+            height,
+            meteors: Vec::new(),
+        }
+    }
+
+    /// Launches `count` meteors from the upper part of the frame, all heading
+    /// roughly the same way like a real shower.
+    pub fn burst(&mut self, count: usize) {
+        let heading: f64 = self.rng.gen_range(0.3..0.9);
+        for _ in 0..count {
+            let angle = heading + self.rng.gen_range(-0.1..0.1);
+            let speed = self.rng.gen_range(8.0..16.0);
+            self.meteors.push((
+                self.rng.gen_range(-0.2..0.8) * self.width,
+                self.rng.gen_range(-0.1..0.4) * self.height,
+                angle.cos() * speed,
+                angle.sin() * speed,
+                // Stagger the starts so the burst doesn't arrive as one line
+                -self.rng.gen_range(0.0..30.0),
+                self.rng.gen_range(40.0..80.0),
+            ));
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        for meteor in &mut self.meteors {
+            meteor.4 += dt;
+            if meteor.4 > 0.0 {
+                meteor.0 += meteor.2 * dt;
+                meteor.1 += meteor.3 * dt;
+            }
+        }
+        self.meteors.retain(|meteor| meteor.4 < meteor.5);
+    }
+
+    pub fn draw(&self, context: &CanvasRenderingContext2d) {
+        context.set_line_cap("round");
+        for &(x, y, vx, vy, age, lifetime) in &self.meteors {
+            if age <= 0.0 {
+                continue;
+            }
+            // Quick flare up, long fade out
+            let life = age / lifetime;
+            let alpha = (life * 8.0).min(1.0) * (1.0 - life);
+            let (tail_x, tail_y) = (x - vx * 6.0, y - vy * 6.0);
+
+            let gradient = context.create_linear_gradient(x, y, tail_x, tail_y);
+            gradient.add_color_stop(0.0, &format!("rgba(255, 255, 255, {})", alpha)).unwrap();
+            gradient.add_color_stop(1.0, "rgba(255, 255, 255, 0)").unwrap();
+            context.set_stroke_style_canvas_gradient(&gradient);
+            context.set_line_width(1.5);
+            context.begin_path();
+            context.move_to(x, y);
+            context.line_to(tail_x, tail_y);
+            context.stroke();
+        }
+        context.set_line_cap("butt");
+    }
+}
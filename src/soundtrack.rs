@@ -0,0 +1,346 @@
+//! Ambient soundtrack synthesized with the Web Audio API: a low drone whose
+//! pitch and brightness follow the warp speed, wind that picks up with it,
+//! a whoosh on every change of effect and chimes for shooting stars. No
+//! audio files; everything is generated here.
+//!
+//! Muted until the listener turns it on, which browsers require anyway
+//! before a page may play sound.
+
+use crate::random;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::cell::RefCell;
+use std::rc::Rc;
+use web_sys::{
+    AudioBuffer, AudioContext, AudioNode, AudioParam, AudioScheduledSourceNode, BiquadFilterNode, BiquadFilterType,
+    GainNode, OscillatorNode, OscillatorType,
+};
+use yew::prelude::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SoundtrackConfig {
+    /// Overall level (0.0..1.0).
+    pub volume: f64,
+    /// Drone pitch in Hz at normal speed. It rises an octave each time the
+    /// speed quadruples.
+    pub pitch: f64,
+}
+
+impl Default for SoundtrackConfig {
+    fn default() -> Self {
+        SoundtrackConfig {
+            volume: 0.5,
+            pitch: 55.0,
+        }
+    }
+}
+
+// A pentatonic scale over two octaves, in semitones above A4, so any
+// handful of chimes sounds right together
+const CHIME_NOTES: [f64; 10] = [0.0, 2.0, 4.0, 7.0, 9.0, 12.0, 14.0, 16.0, 19.0, 21.0];
+
+// Seconds for the drone and wind to settle on a new speed
+const GLIDE: f64 = 0.15;
+
+struct Engine {
+    context: AudioContext,
+    master: GainNode,
+    drone: Vec<OscillatorNode>,
+    drone_filter: BiquadFilterNode,
+    wind_filter: BiquadFilterNode,
+    wind_gain: GainNode,
+    noise: AudioBuffer,
+    rng: StdRng,
+}
+
+struct SoundtrackState {
+    config: SoundtrackConfig,
+    muted: bool,
+    // Speed last passed to the drone
+    speed: f64,
+    engine: Option<Engine>,
+}
+
+/// The soundtrack's controls, shared between a `Starfield` that drives it
+/// (its `soundtrack` prop) and a `SoundToggle` that unmutes it.
+#[derive(Clone)]
+pub struct Soundtrack(Rc<RefCell<SoundtrackState>>);
+
+impl PartialEq for Soundtrack {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Default for Soundtrack {
+    fn default() -> Self {
+        Soundtrack::new(SoundtrackConfig::default())
+    }
+}
+
+impl Soundtrack {
+    /// A muted soundtrack. Nothing is set up until it's first unmuted.
+    pub fn new(config: SoundtrackConfig) -> Self {
+        Soundtrack(Rc::new(RefCell::new(SoundtrackState {
+            config,
+            muted: true,
+            speed: 1.0,
+            engine: None,
+        })))
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.0.borrow().muted
+    }
+
+    /// Fades the sound in or out. Unmute from an input handler such as a
+    /// click; browsers keep audio suspended until the page gets one.
+    pub fn set_muted(&self, muted: bool) {
+        let mut state = self.0.borrow_mut();
+        state.muted = muted;
+        if !muted && state.engine.is_none() {
+            let (config, speed) = (state.config.clone(), state.speed);
+            state.engine = Engine::new(&config, speed);
+        }
+        let volume = if muted { 0.0 } else { state.config.volume };
+        if let Some(engine) = state.engine.as_ref() {
+            if !muted {
+                let _ = engine.context.resume();
+                // Speed changes aren't scheduled while muted, so catch up
+                engine.set_speed(state.config.pitch, state.speed);
+            }
+            glide(&engine.master.gain(), volume, engine.now(), 0.3);
+        }
+    }
+
+    pub fn toggle(&self) {
+        self.set_muted(!self.is_muted());
+    }
+
+    /// Follows the effect's playback rate.
+    pub(crate) fn set_speed(&self, speed: f64) {
+        let mut state = self.0.borrow_mut();
+        // Only worth scheduling when it's audibly different
+        if (speed - state.speed).abs() < state.speed.abs() * 0.01 + 1e-3 {
+            return;
+        }
+        state.speed = speed;
+        let pitch = state.config.pitch;
+        if let Some(engine) = state.engine.as_ref().filter(|_| !state.muted) {
+            engine.set_speed(pitch, speed);
+        }
+    }
+
+    /// A sweep of filtered noise, for a change of effect.
+    pub(crate) fn whoosh(&self) {
+        let state = self.0.borrow();
+        if let Some(engine) = state.engine.as_ref().filter(|_| !state.muted) {
+            engine.whoosh();
+        }
+    }
+
+    /// A short run of chimes for a burst of `count` shooting stars.
+    pub(crate) fn chime(&self, count: usize) {
+        let mut state = self.0.borrow_mut();
+        let muted = state.muted;
+        if let Some(engine) = state.engine.as_mut().filter(|_| !muted) {
+            engine.chime(count);
+        }
+    }
+}
+
+impl Engine {
+    // `None` where Web Audio isn't available
+    fn new(config: &SoundtrackConfig, speed: f64) -> Option<Self> {
+        let context = AudioContext::new().ok()?;
+        let master = context.create_gain().unwrap();
+        master.gain().set_value(0.0);
+        connect(&master, &context.destination());
+
+        // Two detuned saws and a sine an octave down, through a low-pass
+        // that opens up with the speed
+        let drone_filter = context.create_biquad_filter().unwrap();
+        drone_filter.set_type(BiquadFilterType::Lowpass);
+        drone_filter.q().set_value(4.0);
+        let drone_gain = context.create_gain().unwrap();
+        drone_gain.gain().set_value(0.12);
+        connect(&drone_filter, &drone_gain);
+        connect(&drone_gain, &master);
+        let drone = [(OscillatorType::Sawtooth, 7.0), (OscillatorType::Sawtooth, -7.0), (OscillatorType::Sine, -1200.0)]
+            .into_iter()
+            .map(|(wave, detune)| {
+                let oscillator = context.create_oscillator().unwrap();
+                oscillator.set_type(wave);
+                oscillator.detune().set_value(detune as f32);
+                connect(&oscillator, &drone_filter);
+                oscillator.start().unwrap();
+                oscillator
+            })
+            .collect();
+
+        let mut rng = random::rng();
+        let noise = noise_buffer(&context, &mut rng);
+        let wind_filter = context.create_biquad_filter().unwrap();
+        wind_filter.set_type(BiquadFilterType::Bandpass);
+        wind_filter.q().set_value(0.7);
+        let wind_gain = context.create_gain().unwrap();
+        let wind = context.create_buffer_source().unwrap();
+        wind.set_buffer(Some(&noise));
+        wind.set_loop(true);
+        connect(&wind, &wind_filter);
+        connect(&wind_filter, &wind_gain);
+        connect(&wind_gain, &master);
+        wind.start().unwrap();
+
+        let engine = Engine {
+            context,
+            master,
+            drone,
+            drone_filter,
+            wind_filter,
+            wind_gain,
+            noise,
+            rng,
+        };
+        engine.set_speed(config.pitch, speed);
+        Some(engine)
+    }
+
+    fn now(&self) -> f64 {
+        self.context.current_time()
+    }
+
+    fn set_speed(&self, pitch: f64, speed: f64) {
+        let (now, speed) = (self.now(), speed.clamp(0.05, 8.0));
+        // An octave for every quadrupling, so fast warps don't shriek
+        let frequency = pitch * speed.sqrt();
+        for oscillator in &self.drone {
+            glide(&oscillator.frequency(), frequency, now, GLIDE);
+        }
+        glide(&self.drone_filter.frequency(), 150.0 + 650.0 * speed, now, GLIDE);
+        glide(&self.wind_filter.frequency(), 300.0 + 500.0 * speed, now, GLIDE);
+        glide(&self.wind_gain.gain(), 0.02 + 0.03 * speed.min(4.0), now, GLIDE);
+    }
+
+    fn whoosh(&self) {
+        let now = self.now();
+        let source = self.context.create_buffer_source().unwrap();
+        source.set_buffer(Some(&self.noise));
+        let filter = self.context.create_biquad_filter().unwrap();
+        filter.set_type(BiquadFilterType::Bandpass);
+        filter.q().set_value(2.0);
+        let sweep = filter.frequency();
+        sweep.set_value_at_time(200.0, now).unwrap();
+        sweep.exponential_ramp_to_value_at_time(3000.0, now + 0.6).unwrap();
+        sweep.exponential_ramp_to_value_at_time(300.0, now + 1.6).unwrap();
+        let gain = self.context.create_gain().unwrap();
+        let level = gain.gain();
+        level.set_value_at_time(0.0, now).unwrap();
+        level.linear_ramp_to_value_at_time(0.3, now + 0.5).unwrap();
+        level.linear_ramp_to_value_at_time(0.0, now + 1.6).unwrap();
+        connect(&source, &filter);
+        connect(&filter, &gain);
+        connect(&gain, &self.master);
+        play(&source, now, 1.7);
+    }
+
+    fn chime(&mut self, count: usize) {
+        let now = self.now();
+        for i in 0..count.clamp(1, 4) {
+            let note = CHIME_NOTES[self.rng.gen_range(0..CHIME_NOTES.len())];
+            let frequency = 440.0 * 2f64.powf(note / 12.0);
+            let start = now + i as f64 * 0.15 + self.rng.gen_range(0.0..0.05);
+            // A sine with a quiet inharmonic partial, for a bell-like ring
+            for (ratio, level) in [(1.0, 0.08), (2.76, 0.02)] {
+                let oscillator = self.context.create_oscillator().unwrap();
+                oscillator.frequency().set_value((frequency * ratio) as f32);
+                let gain = self.context.create_gain().unwrap();
+                let envelope = gain.gain();
+                envelope.set_value_at_time(0.0, start).unwrap();
+                envelope.linear_ramp_to_value_at_time(level as f32, start + 0.01).unwrap();
+                envelope.exponential_ramp_to_value_at_time(0.0001, start + 2.5 / ratio).unwrap();
+                connect(&oscillator, &gain);
+                connect(&gain, &self.master);
+                play(&oscillator, start, 2.5 / ratio);
+            }
+        }
+    }
+}
+
+fn connect(from: &AudioNode, to: &AudioNode) {
+    from.connect_with_audio_node(to).unwrap();
+}
+
+// Moves `param` to `value` smoothly from `now`, `time` being roughly how
+// long it takes
+fn glide(param: &AudioParam, value: f64, now: f64, time: f64) {
+    param.cancel_scheduled_values(now).unwrap();
+    param.set_target_at_time(value as f32, now, time / 3.0).unwrap();
+}
+
+fn play(source: &AudioScheduledSourceNode, start: f64, seconds: f64) {
+    source.start_with_when(start).unwrap();
+    source.stop_with_when(start + seconds).unwrap();
+}
+
+// Two seconds of white noise, looped for the wind and reused for whooshes
+fn noise_buffer(context: &AudioContext, rng: &mut StdRng) -> AudioBuffer {
+    let rate = context.sample_rate();
+    let samples: Vec<f32> = (0..(rate * 2.0) as usize).map(|_| rng.gen_range(-1.0..1.0)).collect();
+    let buffer = context.create_buffer(1, samples.len() as u32, rate).unwrap();
+    buffer.copy_to_channel(&samples, 0).unwrap();
+    buffer
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct SoundToggleProps {
+    pub soundtrack: Soundtrack,
+}
+
+/// A button that turns the soundtrack on and off.
+#[function_component(SoundToggle)]
+pub fn sound_toggle(props: &SoundToggleProps) -> Html {
+    let muted = use_state(|| props.soundtrack.is_muted());
+    let onclick = {
+        let (soundtrack, muted) = (props.soundtrack.clone(), muted.clone());
+        Callback::from(move |_| {
+            soundtrack.toggle();
+            muted.set(soundtrack.is_muted());
+        })
+    };
+
+    html! {
+        <>
+            <style>
+                {r#"
+                    .sound-toggle {
+                        position: fixed;
+                        right: 1.25rem;
+                        bottom: 1.25rem;
+                        z-index: 5;
+                        padding: 0.6rem 1rem;
+                        border: 1px solid rgba(0, 255, 200, 0.3);
+                        border-radius: 10px;
+                        background: rgba(0, 20, 20, 0.6);
+                        color: white;
+                        font-size: 0.9rem;
+                        cursor: pointer;
+                    }
+                    .sound-toggle:focus-visible {
+                        outline: none;
+                        box-shadow: 0 0 0 3px rgba(0, 255, 200, 0.5);
+                    }
+                "#}
+            </style>
+            <button
+                type="button"
+                class="sound-toggle"
+                aria-label="Ambient sound"
+                aria-pressed={(!*muted).to_string()}
+                {onclick}
+            >
+                { if *muted { "♪ Sound off" } else { "♪ Sound on" } }
+            </button>
+        </>
+    }
+}
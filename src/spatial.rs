@@ -0,0 +1,135 @@
+/// Uniform grid over a fixed set of points for nearest-neighbour queries.
+pub struct SpatialGrid {
+    cell: f64,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+    points: Vec<(f64, f64)>,
+}
+
+impl SpatialGrid {
+    pub fn new(points: Vec<(f64, f64)>, width: f64, height: f64, cell: f64) -> Self {
+        let cols = ((width / cell).ceil() as usize).max(1);
+        let rows = ((height / cell).ceil() as usize).max(1);
+        let mut grid = SpatialGrid {
+            cell,
+            cols,
+            rows,
+            cells: vec![Vec::new(); cols * rows],
+            points,
+        };
+        for i in 0..grid.points.len() {
+            let (col, row) = grid.cell_of(grid.points[i].0, grid.points[i].1);
+            grid.cells[row * cols + col].push(i);
+        }
+        grid
+    }
+
+    fn cell_of(&self, x: f64, y: f64) -> (usize, usize) {
+        let col = ((x / self.cell).max(0.0) as usize).min(self.cols - 1);
+        let row = ((y / self.cell).max(0.0) as usize).min(self.rows - 1);
+        (col, row)
+    }
+
+    /// Index of the point closest to `(x, y)` within `max_distance`.
+    pub fn nearest(&self, x: f64, y: f64, max_distance: f64) -> Option<usize> {
+        self.nearest_among(x, y, max_distance, self.points.len())
+    }
+
+    /// Like `nearest`, but only among the first `count` points.
+    pub fn nearest_among(&self, x: f64, y: f64, max_distance: f64, count: usize) -> Option<usize> {
+        let (col, row) = self.cell_of(x, y);
+        let mut best: Option<(usize, f64)> = None;
+
+        // Search rings of cells outwards until nothing closer can remain
+        let max_ring = self.cols.max(self.rows);
+        for ring in 0..=max_ring {
+            let ring_distance = (ring as f64 - 1.0).max(0.0) * self.cell;
+            if ring_distance > max_distance || best.is_some_and(|(_, d)| d < ring_distance) {
+                break;
+            }
+
+            let (min_col, max_col) = (col as i64 - ring as i64, col as i64 + ring as i64);
+            let (min_row, max_row) = (row as i64 - ring as i64, row as i64 + ring as i64);
+            for r in min_row.max(0)..=max_row.min(self.rows as i64 - 1) {
+                for c in min_col.max(0)..=max_col.min(self.cols as i64 - 1) {
+                    // Only the outline of the ring is new
+                    if r != min_row && r != max_row && c != min_col && c != max_col {
+                        continue;
+                    }
+                    for &i in self.cells[r as usize * self.cols + c as usize].iter().filter(|&&i| i < count) {
+                        let (px, py) = self.points[i];
+                        let d = ((px - x).powi(2) + (py - y).powi(2)).sqrt();
+                        if d <= max_distance && !best.is_some_and(|(_, best_d)| best_d <= d) {
+                            best = Some((i, d));
+                        }
+                    }
+                }
+            }
+        }
+
+        best.map(|(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn brute_force(points: &[(f64, f64)], x: f64, y: f64, max_distance: f64, count: usize) -> Option<usize> {
+        points
+            .iter()
+            .take(count)
+            .enumerate()
+            .map(|(i, &(px, py))| (i, ((px - x).powi(2) + (py - y).powi(2)).sqrt()))
+            .filter(|&(_, d)| d <= max_distance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    fn random_points(rng: &mut StdRng, n: usize) -> Vec<(f64, f64)> {
+        (0..n).map(|_| (rng.gen_range(0.0..800.0), rng.gen_range(0.0..600.0))).collect()
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let points = random_points(&mut rng, 300);
+        let grid = SpatialGrid::new(points.clone(), 800.0, 600.0, 40.0);
+        for _ in 0..500 {
+            let (x, y) = (rng.gen_range(-50.0..850.0), rng.gen_range(-50.0..650.0));
+            for max_distance in [16.0, 100.0, f64::INFINITY] {
+                assert_eq!(
+                    grid.nearest(x, y, max_distance),
+                    brute_force(&points, x, y, max_distance, points.len()),
+                    "at ({}, {}) within {}",
+                    x,
+                    y,
+                    max_distance
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_among_skips_later_points() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let points = random_points(&mut rng, 300);
+        let grid = SpatialGrid::new(points.clone(), 800.0, 600.0, 40.0);
+        for _ in 0..200 {
+            let (x, y) = (rng.gen_range(0.0..800.0), rng.gen_range(0.0..600.0));
+            assert_eq!(grid.nearest_among(x, y, 60.0, 100), brute_force(&points, x, y, 60.0, 100));
+        }
+        assert_eq!(grid.nearest_among(400.0, 300.0, f64::INFINITY, 0), None);
+    }
+
+    #[test]
+    fn out_of_range_is_none() {
+        let grid = SpatialGrid::new(vec![(10.0, 10.0)], 100.0, 100.0, 20.0);
+        assert_eq!(grid.nearest(90.0, 90.0, 16.0), None);
+        assert_eq!(grid.nearest(20.0, 10.0, 16.0), Some(0));
+        assert_eq!(SpatialGrid::new(Vec::new(), 100.0, 100.0, 20.0).nearest(0.0, 0.0, f64::INFINITY), None);
+    }
+}
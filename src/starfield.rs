@@ -0,0 +1,706 @@
+use crate::bloom::{Bloom, BloomConfig};
+use crate::canvas::context_2d;
+use crate::capture::{save_png, still_canvas, Capture, Recording};
+use crate::effects::{
+    BlackHole, BlackHoleConfig, Constellation, Effect, Galaxy, GalaxyConfig, Gravity, GravityConfig,
+    MorphConfig, Pointer, Sky, SkyConfig, Spiral, Tunnel, TunnelConfig, Twinkle, Warp, WarpConfig,
+};
+use crate::game::{Asteroids, GameControls};
+use crate::nebula::{Nebula, NebulaConfig};
+use crate::optics::OpticsConfig;
+use crate::overlay::DebugOverlay;
+use crate::preset::{Preset, PresetSource};
+use crate::quality::{Detail, QualityConfig, QualityGovernor, QualityTier};
+use crate::random;
+use crate::reactive::AudioReactive;
+use crate::shape::DistributionConfig;
+use crate::shooting_stars::ShootingStars;
+use crate::soundtrack::Soundtrack;
+use crate::timeline::{Param, Timeline, TimelineEvent};
+use crate::trails::Trails;
+use crate::tween::{Transition, Tween};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlCanvasElement, KeyboardEvent};
+use yew::prelude::*;
+
+// Mixed into the seed for the nebula's own stream, so the scene and the
+// nebula can each be rebuilt alone and still match a fresh start
+const NEBULA_STREAM: u64 = 0x6e65_6275_6c61;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StarEffect {
+    Warp,
+    Twinkle,
+    Spiral,
+    BlackHole,
+    Tunnel,
+    Gravity,
+    Sky,
+}
+
+impl StarEffect {
+    pub const ALL: [StarEffect; 7] = [
+        StarEffect::Warp,
+        StarEffect::Twinkle,
+        StarEffect::Spiral,
+        StarEffect::BlackHole,
+        StarEffect::Tunnel,
+        StarEffect::Gravity,
+        StarEffect::Sky,
+    ];
+
+    /// Name used in presets and URLs.
+    pub fn name(self) -> &'static str {
+        match self {
+            StarEffect::Warp => "warp",
+            StarEffect::Twinkle => "twinkle",
+            StarEffect::Spiral => "spiral",
+            StarEffect::BlackHole => "black_hole",
+            StarEffect::Tunnel => "tunnel",
+            StarEffect::Gravity => "gravity",
+            StarEffect::Sky => "sky",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        StarEffect::ALL.into_iter().find(|effect| effect.name() == name)
+    }
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct StarfieldProps {
+    #[prop_or(StarEffect::Warp)]
+    pub effect: StarEffect,
+    /// Replaces `effect` and fills in every setting the props leave unset.
+    /// Unknown preset names are ignored.
+    #[prop_or_default]
+    pub preset: Option<PresetSource>,
+    /// Procedural nebula drawn underneath the effect.
+    #[prop_or_default]
+    pub nebula: Option<NebulaConfig>,
+    #[prop_or_default]
+    pub warp: Option<WarpConfig>,
+    /// Replaces the classic `Spiral` arms with a density-wave galaxy.
+    #[prop_or_default]
+    pub galaxy: Option<GalaxyConfig>,
+    #[prop_or_default]
+    pub black_hole: Option<BlackHoleConfig>,
+    #[prop_or_default]
+    pub tunnel: Option<TunnelConfig>,
+    #[prop_or_default]
+    pub gravity: Option<GravityConfig>,
+    #[prop_or_default]
+    pub sky: Option<SkyConfig>,
+    /// Figures drawn between `Twinkle` stars.
+    #[prop_or_default]
+    pub constellations: Vec<Constellation>,
+    /// Periodically gathers the `Warp` stars into text or an image.
+    #[prop_or_default]
+    pub morph: Option<MorphConfig>,
+    /// Lays the `Twinkle` stars out to follow an image.
+    #[prop_or_default]
+    pub distribution: Option<DistributionConfig>,
+    /// Keeps previous frames and fades them out, leaving motion trails.
+    /// The value is the share of brightness kept per frame, e.g. 0.85.
+    #[prop_or_default]
+    pub trails: Option<f64>,
+    /// Makes bright stars and glows bleed light into their surroundings.
+    #[prop_or_default]
+    pub bloom: Option<BloomConfig>,
+    /// Diffraction spikes, halos and lens flare on `Twinkle`'s brightest
+    /// stars and the `Spiral` core.
+    #[prop_or_default]
+    pub optics: Option<OpticsConfig>,
+    /// Lowers star counts, glow, post-processing and resolution when frames
+    /// miss the display's refresh, and raises them again when there's
+    /// headroom. Read
+    /// once on mount.
+    #[prop_or_default]
+    pub quality: Option<QualityConfig>,
+    /// Called with the quality tier whenever it changes, and once on start.
+    #[prop_or_default]
+    pub on_quality: Callback<QualityTier>,
+    /// Playback rate of the effect, nebula and trails; 1.0 is normal speed.
+    #[prop_or(1.0)]
+    pub speed: f64,
+    /// Share of the stars drawn (0.0..1.0), on top of what the quality tier
+    /// leaves out.
+    #[prop_or(1.0)]
+    pub density: f64,
+    /// Star brightness relative to normal, for effects that support it
+    /// (`Twinkle`).
+    #[prop_or(1.0)]
+    pub brightness: f64,
+    /// Eases changes to `speed`, `density` and `brightness` and cross-fades nebula
+    /// changes, instead of switching at once.
+    #[prop_or_default]
+    pub transition: Option<Transition>,
+    /// Scripted cuts, parameter changes and events, overriding `effect`,
+    /// `speed`, `density` and `brightness` while it has settings for them.
+    #[prop_or_default]
+    pub timeline: Option<Timeline>,
+    /// Called with the name of each `TimelineEvent::Marker` the timeline
+    /// passes.
+    #[prop_or_default]
+    pub on_marker: Callback<String>,
+    /// Records the animation to a video or GIF, or saves a screenshot.
+    #[prop_or_default]
+    pub capture: Option<Capture>,
+    /// Ambient sound following the speed, effect changes and shooting stars.
+    #[prop_or_default]
+    pub soundtrack: Option<Soundtrack>,
+    /// Drives settings from live audio, for those the timeline leaves alone.
+    #[prop_or_default]
+    pub reactive: Option<AudioReactive>,
+    /// Seed for every random layout; a random one is picked when unset.
+    #[prop_or_default]
+    pub seed: Option<u64>,
+    /// Shows the debug overlay from the start. Alt+Shift+D toggles it.
+    #[prop_or_default]
+    pub debug: bool,
+}
+
+impl StarfieldProps {
+    /// These props with the preset, if any, merged in.
+    fn resolved(&self) -> StarfieldProps {
+        let preset = match &self.preset {
+            Some(PresetSource::Named(name)) => Preset::named(name),
+            Some(PresetSource::Inline(preset)) => Some(preset.as_ref().clone()),
+            None => None,
+        };
+        let Some(preset) = preset else {
+            return self.clone();
+        };
+
+        let mut props = self.clone();
+        props.preset = None;
+        props.effect = preset.effect;
+        props.seed = props.seed.or(preset.seed);
+        props.warp = props.warp.or(preset.warp);
+        props.morph = props.morph.or(preset.morph);
+        props.galaxy = props.galaxy.or(preset.galaxy);
+        props.black_hole = props.black_hole.or(preset.black_hole);
+        props.tunnel = props.tunnel.or(preset.tunnel);
+        props.gravity = props.gravity.or(preset.gravity);
+        props.sky = props.sky.or(preset.sky);
+        if props.constellations.is_empty() {
+            props.constellations = preset.constellations;
+        }
+        props.distribution = props.distribution.or(preset.distribution);
+        props.nebula = props.nebula.or(preset.nebula);
+        props.trails = props.trails.or(preset.trails);
+        props.bloom = props.bloom.or(preset.bloom);
+        props.optics = props.optics.or(preset.optics);
+        props
+    }
+
+    /// Whether the effect has to be rebuilt to pick up `other`'s settings.
+    fn scene_differs(&self, other: &StarfieldProps) -> bool {
+        self.effect != other.effect
+            || self.warp != other.warp
+            || self.galaxy != other.galaxy
+            || self.black_hole != other.black_hole
+            || self.tunnel != other.tunnel
+            || self.gravity != other.gravity
+            || self.sky != other.sky
+            || self.constellations != other.constellations
+            || self.morph != other.morph
+            || self.distribution != other.distribution
+            || self.optics != other.optics
+            // Spiral only draws its own trails without the trail layer
+            || self.trails.is_some() != other.trails.is_some()
+    }
+
+    fn create_effect(&self, width: f64, height: f64) -> Box<dyn Effect> {
+        match self.effect {
+            StarEffect::Warp => Box::new(Warp::new(self.warp.clone().unwrap_or_default(), self.morph.clone(), width, height)),
+            StarEffect::Twinkle => Box::new(Twinkle::new(
+                self.constellations.clone(),
+                self.distribution.clone(),
+                self.optics.clone(),
+                width,
+                height,
+            )),
+            StarEffect::Spiral => match &self.galaxy {
+                Some(galaxy) => Box::new(Galaxy::new(galaxy.clone(), width, height)),
+                None => Box::new(Spiral::new(self.trails.is_some(), self.optics.clone(), width, height)),
+            },
+            StarEffect::BlackHole => Box::new(BlackHole::new(self.black_hole.clone().unwrap_or_default(), width, height)),
+            StarEffect::Tunnel => Box::new(Tunnel::new(self.tunnel.clone().unwrap_or_default(), width, height)),
+            StarEffect::Gravity => Box::new(Gravity::new(self.gravity.clone().unwrap_or_default(), width, height)),
+            StarEffect::Sky => Box::new(Sky::new(self.sky.clone().unwrap_or_default(), width, height)),
+        }
+    }
+}
+
+#[function_component(Starfield)]
+pub fn starfield(props: &StarfieldProps) -> Html {
+    let canvas_ref = use_node_ref();
+    // Pointer events are queued here and handed to the effect on the next frame
+    let pointer_events = use_mut_ref(Vec::<Pointer>::new);
+    // The render loop picks up prop changes from here, so the running
+    // animation can be reconfigured without remounting the canvas
+    let props = props.resolved();
+    let latest = use_mut_ref(|| props.clone());
+    *latest.borrow_mut() = props.clone();
+    // The hidden game; the canvas moves on top of the page while it runs
+    let game_controls = use_memo((), |_| GameControls::default());
+    let playing = use_state_eq(|| false);
+
+    {
+        let game_controls = (*game_controls).clone();
+        use_effect_with((), move |_| game_controls.listen());
+    }
+
+    {
+        let canvas_ref = canvas_ref.clone();
+        let pointer_events = pointer_events.clone();
+        let (game_controls, playing) = ((*game_controls).clone(), playing.clone());
+        use_effect_with((), move |_| {
+            let canvas = canvas_ref.cast::<HtmlCanvasElement>().unwrap();
+            let context = context_2d(&canvas);
+
+            let window = web_sys::window().unwrap();
+            let width = window.inner_width().unwrap().as_f64().unwrap();
+            let height = window.inner_height().unwrap().as_f64().unwrap();
+
+            let mut seed = props.seed.unwrap_or_else(random::random_seed);
+            random::reseed(seed);
+
+            let mut scene = props.create_effect(width, height);
+            random::reseed(seed ^ NEBULA_STREAM);
+            let mut background = props.nebula.as_ref().map(|config| Nebula::new(config, width, height));
+            let mut trails = props.trails.map(|decay| Trails::new(decay, width, height));
+            let mut bloom = props.bloom.clone().map(|config| Bloom::new(config, width, height));
+            let mut shooting_stars = ShootingStars::new(width, height);
+            // Nebula being faded out, and the opacity of the one replacing it
+            let mut previous_background: Option<Nebula> = None;
+            let mut nebula_fade = Tween::still(1.0);
+            let mut speed = Tween::still(props.speed);
+            let mut density = Tween::still(props.density);
+            let mut shown_density = props.density;
+            let mut brightness = Tween::still(props.brightness);
+            let mut shown_brightness = props.brightness;
+            scene.set_brightness(shown_brightness);
+
+            let mut governor = props.quality.clone().map(QualityGovernor::new);
+            let mut tier = governor.as_ref().map_or(QualityTier::High, |governor| governor.tier());
+            // Effects keep drawing in CSS pixels; only the backing store shrinks
+            let apply_tier = {
+                let (canvas, context, latest) = (canvas.clone(), context.clone(), latest.clone());
+                move |tier: QualityTier, density: f64, scene: &mut Box<dyn Effect>| {
+                    let resolution = tier.resolution();
+                    canvas.set_width((width * resolution) as u32);
+                    canvas.set_height((height * resolution) as u32);
+                    context.scale(resolution, resolution).unwrap();
+                    scene.set_detail(detail(tier, density));
+                    let on_quality = latest.borrow().on_quality.clone();
+                    on_quality.emit(tier);
+                }
+            };
+            apply_tier(tier, shown_density, &mut scene);
+
+            let show_overlay = Rc::new(Cell::new(props.debug));
+            let debug_toggle = listen_for_debug_toggle(show_overlay.clone());
+            let mut overlay: Option<DebugOverlay> = None;
+            let mut recording: Option<Recording> = None;
+            let mut game: Option<Asteroids> = None;
+
+            let mut current = props;
+            let performance = window.performance().unwrap();
+            let running = start_animation(move |dt| {
+                let mut next = latest.borrow().clone();
+
+                if let Some(capture) = next.capture.as_ref() {
+                    if let Some(config) = capture.take_request() {
+                        recording = Some(Recording::start(capture.clone(), config, &canvas, width, height));
+                    }
+                }
+                // Recordings step the animation at their own fixed rate
+                let dt = match recording.as_mut() {
+                    Some(recording) => match recording.step(dt) {
+                        Some(step) => step,
+                        None => return,
+                    },
+                    None => dt,
+                };
+                // Timeline tracks and live audio skip the transition; they're already smooth
+                let (mut scripted_speed, mut scripted_density, mut scripted_brightness) = (None, None, None);
+                let mut scripted_opacity = None;
+                if let Some(timeline) = next.timeline.clone() {
+                    for event in timeline.advance(dt / 60.0) {
+                        match event {
+                            TimelineEvent::ShootingStars(count) => {
+                                shooting_stars.burst(count);
+                                if let Some(soundtrack) = next.soundtrack.as_ref() {
+                                    soundtrack.chime(count);
+                                }
+                            }
+                            TimelineEvent::Marker(name) => next.on_marker.emit(name),
+                            // Applied below from the playhead, so seeking picks them up too
+                            TimelineEvent::Cut(_) => {}
+                        }
+                    }
+                    let time = timeline.time();
+                    next.effect = timeline.effect_at(time).unwrap_or(next.effect);
+                    scripted_speed = timeline.value_at(Param::Speed, time);
+                    scripted_density = timeline.value_at(Param::Density, time);
+                    scripted_brightness = timeline.value_at(Param::Brightness, time);
+                    scripted_opacity = timeline.value_at(Param::Opacity, time);
+                }
+                if let Some(reactive) = next.reactive.as_ref() {
+                    for (param, value) in reactive.values(dt) {
+                        let scripted = match param {
+                            Param::Speed => &mut scripted_speed,
+                            Param::Density => &mut scripted_density,
+                            Param::Brightness => &mut scripted_brightness,
+                            Param::Opacity => &mut scripted_opacity,
+                        };
+                        scripted.get_or_insert(value);
+                    }
+                }
+
+                if next != current {
+                    if next.seed != current.seed {
+                        seed = next.seed.unwrap_or_else(random::random_seed);
+                    }
+                    // Rebuilt from the same seed, so the layout stays put while
+                    // parameters change
+                    if next.seed != current.seed || next.scene_differs(&current) {
+                        random::reseed(seed);
+                        scene = next.create_effect(width, height);
+                        scene.set_detail(detail(tier, shown_density));
+                        scene.set_brightness(shown_brightness);
+                        if let Some(soundtrack) = next.soundtrack.as_ref().filter(|_| next.effect != current.effect) {
+                            soundtrack.whoosh();
+                        }
+                    }
+                    if next.seed != current.seed || next.nebula != current.nebula {
+                        if next.nebula != current.nebula {
+                            previous_background = background.take().filter(|_| next.transition.is_some());
+                            nebula_fade = Tween::still(0.0);
+                            nebula_fade.retarget(1.0, next.transition);
+                        }
+                        random::reseed(seed ^ NEBULA_STREAM);
+                        background = next.nebula.as_ref().map(|config| Nebula::new(config, width, height));
+                    }
+                    if next.speed != current.speed {
+                        speed.retarget(next.speed, next.transition);
+                    }
+                    if next.density != current.density {
+                        density.retarget(next.density, next.transition);
+                    }
+                    if next.brightness != current.brightness {
+                        brightness.retarget(next.brightness, next.transition);
+                    }
+                    if next.trails != current.trails {
+                        trails = next.trails.map(|decay| Trails::new(decay, width, height));
+                    }
+                    if next.bloom != current.bloom {
+                        bloom = next.bloom.clone().map(|config| Bloom::new(config, width, height));
+                    }
+                    if next.debug != current.debug {
+                        show_overlay.set(next.debug);
+                    }
+                    current = next;
+                }
+
+                let started = performance.now();
+                speed.update(dt);
+                density.update(dt);
+                brightness.update(dt);
+                nebula_fade.update(dt);
+                let rate = scripted_speed.unwrap_or_else(|| speed.value()).max(0.0);
+                let step = dt * rate;
+                if let Some(soundtrack) = current.soundtrack.as_ref() {
+                    soundtrack.set_speed(rate);
+                }
+                let stars = scripted_density.unwrap_or_else(|| density.value());
+                if stars != shown_density {
+                    shown_density = stars;
+                    scene.set_detail(detail(tier, shown_density));
+                }
+                let shine = scripted_brightness.unwrap_or_else(|| brightness.value());
+                if shine != shown_brightness {
+                    shown_brightness = shine;
+                    scene.set_brightness(shown_brightness);
+                }
+
+                context.set_fill_style_str(scene.background());
+                context.fill_rect(0.0, 0.0, width, height);
+
+                if nebula_fade.is_finished() {
+                    previous_background = None;
+                }
+                let fade = nebula_fade.value();
+                if let Some(nebula) = previous_background.as_mut() {
+                    nebula.update(step);
+                    context.set_global_alpha(1.0 - fade);
+                    nebula.draw(&context);
+                }
+                if let Some(nebula) = background.as_mut() {
+                    nebula.update(step);
+                    context.set_global_alpha(fade);
+                    nebula.draw(&context);
+                }
+                context.set_global_alpha(1.0);
+
+                // Of all the starfields on the page, the one in view gets the game
+                if game_controls.take_request() && game.is_none() && in_view(&canvas, height) {
+                    game = Some(Asteroids::new(width, height));
+                    game_controls.set_playing(true);
+                    playing.set(true);
+                    scene.pointer(Pointer::Leave);
+                }
+                for event in pointer_events.borrow_mut().drain(..) {
+                    // The game has the pointer while it runs
+                    if game.is_none() {
+                        scene.pointer(event);
+                    }
+                }
+                scene.update(step);
+                shooting_stars.update(step);
+
+                let post_processing = tier.post_processing();
+                match trails.as_ref().filter(|_| post_processing) {
+                    Some(trails) => {
+                        trails.fade(step);
+                        scene.draw(trails.context());
+                        shooting_stars.draw(trails.context());
+                        trails.draw(&context);
+                    }
+                    None => {
+                        scene.draw(&context);
+                        shooting_stars.draw(&context);
+                    }
+                }
+
+                if let Some(bloom) = bloom.as_ref().filter(|_| post_processing) {
+                    bloom.apply(&canvas, &context);
+                }
+                scene.draw_overlay(&context);
+
+                // Fades everything drawn so far, whatever the effect, back
+                // towards the background
+                let opacity = scripted_opacity.unwrap_or(1.0).clamp(0.0, 1.0);
+                if opacity < 1.0 {
+                    context.set_global_alpha(1.0 - opacity);
+                    context.set_fill_style_str(scene.background());
+                    context.fill_rect(0.0, 0.0, width, height);
+                    context.set_global_alpha(1.0);
+                }
+
+                if let Some(active) = game.as_mut() {
+                    active.update(dt, game_controls.input());
+                    // The game is laid out for the window it started in, so
+                    // resizing or scrolling the canvas away ends it
+                    let window_width = window.inner_width().unwrap().as_f64().unwrap();
+                    let window_height = window.inner_height().unwrap().as_f64().unwrap();
+                    let moved = window_width != width || window_height != height || !in_view(&canvas, window_height);
+                    if moved {
+                        active.close();
+                    }
+                    if active.is_closed() {
+                        game = None;
+                        game_controls.set_playing(false);
+                        playing.set(false);
+                    } else {
+                        active.draw(&context);
+                    }
+                }
+
+                if let Some(config) = current.capture.as_ref().and_then(Capture::take_screenshot) {
+                    let (still, still_context, scale) = still_canvas(&config, width, height);
+                    still_context.set_fill_style_str(scene.background());
+                    still_context.fill_rect(0.0, 0.0, width, height);
+                    if let Some(nebula) = background.as_ref() {
+                        nebula.draw(&still_context);
+                    }
+                    // Full detail, whatever the device managed on screen
+                    scene.set_detail(detail(QualityTier::High, shown_density));
+                    scene.draw(&still_context);
+                    scene.set_detail(detail(tier, shown_density));
+                    shooting_stars.draw(&still_context);
+                    if let Some(config) = current.bloom.clone() {
+                        // Same glow size relative to the stars
+                        let config = BloomConfig {
+                            radius: config.radius * scale,
+                            ..config
+                        };
+                        still_context.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0).unwrap();
+                        Bloom::new(config, still.width() as f64, still.height() as f64).apply(&still, &still_context);
+                    }
+                    save_png(&still, &config.file_name);
+                }
+
+                if let Some(active) = recording.as_mut() {
+                    if !active.add_frame(&canvas) {
+                        recording.take().unwrap().finish();
+                    }
+                    // Frame times mean nothing while recording, and the
+                    // overlay would end up in the video
+                    return;
+                }
+
+                let (interval, work) = (dt * 1000.0 / 60.0, performance.now() - started);
+                if let Some(governor) = governor.as_mut() {
+                    if let Some(changed) = governor.record(interval, work) {
+                        tier = changed;
+                        apply_tier(tier, shown_density, &mut scene);
+                    }
+                }
+
+                if show_overlay.get() {
+                    // Only patch the contexts for counting while the overlay is shown
+                    let overlay = overlay.get_or_insert_with(DebugOverlay::new);
+                    match trails.as_ref() {
+                        Some(trails) => overlay.instrument(&[&context, trails.context()]),
+                        None => overlay.instrument(&[&context]),
+                    }
+                    overlay.record(interval, work);
+                    let tier = governor.as_ref().map(|governor| governor.tier());
+                    overlay.draw(&context, scene.star_count(), seed, tier);
+                } else {
+                    overlay = None;
+                }
+            });
+
+            move || {
+                running.set(false);
+                web_sys::window()
+                    .unwrap()
+                    .remove_event_listener_with_callback("keydown", debug_toggle.as_ref().unchecked_ref())
+                    .unwrap();
+            }
+        });
+    }
+
+    let on_pointer_down = {
+        let (pointer_events, game_controls) = (pointer_events.clone(), game_controls.clone());
+        Callback::from(move |e: PointerEvent| {
+            game_controls.press(e.offset_x() as f64, e.offset_y() as f64);
+            pointer_events.borrow_mut().push(Pointer::Down {
+                x: e.offset_x() as f64,
+                y: e.offset_y() as f64,
+            });
+        })
+    };
+
+    let on_pointer_move = {
+        let (pointer_events, game_controls) = (pointer_events.clone(), game_controls.clone());
+        Callback::from(move |e: PointerEvent| {
+            game_controls.drag(e.offset_x() as f64, e.offset_y() as f64);
+            pointer_events.borrow_mut().push(Pointer::Move {
+                x: e.offset_x() as f64,
+                y: e.offset_y() as f64,
+            });
+        })
+    };
+
+    let on_pointer_leave = {
+        let (pointer_events, game_controls) = (pointer_events.clone(), game_controls.clone());
+        Callback::from(move |_: PointerEvent| {
+            game_controls.release();
+            pointer_events.borrow_mut().push(Pointer::Leave);
+        })
+    };
+
+    let on_pointer_up = {
+        let game_controls = game_controls.clone();
+        Callback::from(move |_: PointerEvent| game_controls.release())
+    };
+
+    html! {
+        <canvas
+            ref={canvas_ref}
+            class={classes!("starfield-canvas", playing.then_some("playing"))}
+            onpointerdown={on_pointer_down}
+            onpointermove={on_pointer_move}
+            onpointerup={on_pointer_up}
+            onpointerleave={on_pointer_leave}
+        />
+    }
+}
+
+/// The tier's level of detail with only `density` of the stars kept.
+fn detail(tier: QualityTier, density: f64) -> Detail {
+    let detail = tier.detail();
+    Detail {
+        stars: detail.stars * density.clamp(0.0, 1.0),
+        ..detail
+    }
+}
+
+/// Whether `canvas` covers the middle of the window, i.e. is the one being
+/// looked at.
+fn in_view(canvas: &HtmlCanvasElement, window_height: f64) -> bool {
+    let rect = canvas.get_bounding_client_rect();
+    rect.top() <= window_height / 2.0 && rect.bottom() >= window_height / 2.0
+}
+
+/// Toggles `visible` on Alt+Shift+D until the returned listener is removed
+/// from the window.
+fn listen_for_debug_toggle(visible: Rc<Cell<bool>>) -> Closure<dyn FnMut(KeyboardEvent)> {
+    let closure = Closure::<dyn FnMut(KeyboardEvent)>::new(move |e: KeyboardEvent| {
+        // Match the physical key; Alt changes the character on macOS
+        if e.alt_key() && e.shift_key() && e.code() == "KeyD" {
+            visible.set(!visible.get());
+        }
+    });
+    web_sys::window()
+        .unwrap()
+        .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
+        .unwrap();
+    closure
+}
+
+type FrameCallback = Rc<RefCell<Option<Closure<dyn FnMut(f64)>>>>;
+
+/// Runs `frame` on every animation frame, passing the time since the previous
+/// frame in 60 Hz frames (capped so a backgrounded tab doesn't jump ahead).
+/// The loop stops once the returned flag is cleared.
+fn start_animation(mut frame: impl FnMut(f64) + 'static) -> Rc<Cell<bool>> {
+    let animate: FrameCallback = Rc::new(RefCell::new(None));
+    let animate_clone = animate.clone();
+    let mut last_timestamp: Option<f64> = None;
+    let running = Rc::new(Cell::new(true));
+    let keep_running = running.clone();
+
+    *animate.borrow_mut() = Some(Closure::wrap(Box::new(move |timestamp: f64| {
+        if !keep_running.get() {
+            // Drop this closure, and everything the frame holds, with it
+            animate_clone.borrow_mut().take();
+            return;
+        }
+        let dt = match last_timestamp {
+            Some(last) => ((timestamp - last) / (1000.0 / 60.0)).clamp(0.0, 4.0),
+            None => 1.0,
+        };
+        last_timestamp = Some(timestamp);
+
+        frame(dt);
+
+        web_sys::window()
+            .unwrap()
+            .request_animation_frame(
+                animate_clone
+                    .borrow()
+                    .as_ref()
+                    .unwrap()
+                    .as_ref()
+                    .unchecked_ref(),
+            )
+            .unwrap();
+    }) as Box<dyn FnMut(f64)>));
+
+    web_sys::window()
+        .unwrap()
+        .request_animation_frame(animate.borrow().as_ref().unwrap().as_ref().unchecked_ref())
+        .unwrap();
+    running
+}
@@ -0,0 +1,323 @@
+//! Scripted sequences for a `Starfield`: keyframed parameter tracks and
+//! events on a clock that can be played, paused and scrubbed.
+//!
+//! ```text
+//! let intro = Timeline::new(6.0)
+//!     .event(0.0, TimelineEvent::Cut(StarEffect::Twinkle))
+//!     .event(2.0, TimelineEvent::ShootingStars(12))
+//!     .event(3.0, TimelineEvent::Cut(StarEffect::Warp))
+//!     .keyframe(Param::Speed, 3.0, 0.1, Easing::Linear)
+//!     .keyframe(Param::Speed, 5.0, 4.0, Easing::In(Curve::Cubic))
+//!     .event(5.0, TimelineEvent::Cut(StarEffect::Spiral))
+//!     .keyframe(Param::Speed, 5.0, 1.0, Easing::Linear);
+//! ```
+
+use crate::starfield::StarEffect;
+use crate::tween::Easing;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::Element;
+
+/// `Starfield` settings a track can animate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Param {
+    /// Playback rate, see `StarfieldProps::speed`.
+    Speed,
+    /// Share of the stars drawn, see `StarfieldProps::density`.
+    Density,
+    /// Star brightness, see `StarfieldProps::brightness`.
+    Brightness,
+    /// Opacity of the whole frame over its background color, 1.0 when
+    /// nothing drives it.
+    Opacity,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframe {
+    /// Seconds from the start of the timeline.
+    pub time: f64,
+    pub value: f64,
+    /// How the track moves into this keyframe from the one before it.
+    pub easing: Easing,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimelineEvent {
+    /// Switches to another effect until the next cut. Cuts hold when
+    /// seeking, so a scrubbed timeline always shows the right effect.
+    Cut(StarEffect),
+    /// Launches a burst of this many meteors.
+    ShootingStars(usize),
+    /// Passed to `StarfieldProps::on_marker`, to sync the page with the
+    /// sequence.
+    Marker(String),
+}
+
+struct Playhead {
+    time: f64,
+    playing: bool,
+}
+
+/// Keyframed tracks and timed events. Clones share one playhead, so keep
+/// the timeline in a hook like `use_memo` and pause, play or seek it from
+/// callbacks; a timeline rebuilt on every render restarts every time.
+#[derive(Clone)]
+pub struct Timeline {
+    /// Length in seconds.
+    pub duration: f64,
+    /// Starts over at the end instead of stopping.
+    pub looping: bool,
+    // param, keyframes sorted by time
+    tracks: Vec<(Param, Vec<Keyframe>)>,
+    // time, event, sorted by time
+    events: Vec<(f64, TimelineEvent)>,
+    playhead: Rc<RefCell<Playhead>>,
+}
+
+impl PartialEq for Timeline {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.playhead, &other.playhead)
+            && self.duration == other.duration
+            && self.looping == other.looping
+            && self.tracks == other.tracks
+            && self.events == other.events
+    }
+}
+
+impl Timeline {
+    /// An empty timeline, playing from the start.
+    pub fn new(duration: f64) -> Self {
+        Timeline {
+            duration: duration.max(0.0),
+            looping: false,
+            tracks: Vec::new(),
+            events: Vec::new(),
+            playhead: Rc::new(RefCell::new(Playhead { time: 0.0, playing: true })),
+        }
+    }
+
+    pub fn looped(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Adds a keyframe to `param`'s track. Two keyframes at the same time
+    /// make the track jump there.
+    pub fn keyframe(mut self, param: Param, time: f64, value: f64, easing: Easing) -> Self {
+        let keyframe = Keyframe { time, value, easing };
+        match self.tracks.iter_mut().find(|(track, _)| *track == param) {
+            Some((_, keyframes)) => {
+                // After any keyframes at the same time, so insertion order breaks ties
+                let index = keyframes.partition_point(|other| other.time <= time);
+                keyframes.insert(index, keyframe);
+            }
+            None => self.tracks.push((param, vec![keyframe])),
+        }
+        self
+    }
+
+    pub fn event(mut self, time: f64, event: TimelineEvent) -> Self {
+        let index = self.events.partition_point(|(other, _)| *other <= time);
+        self.events.insert(index, (time, event));
+        self
+    }
+
+    pub fn play(&self) {
+        let mut playhead = self.playhead.borrow_mut();
+        // Playing a finished timeline starts it over
+        if !self.looping && playhead.time >= self.duration {
+            playhead.time = 0.0;
+        }
+        playhead.playing = true;
+    }
+
+    pub fn pause(&self) {
+        self.playhead.borrow_mut().playing = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playhead.borrow().playing
+    }
+
+    /// Current position in seconds.
+    pub fn time(&self) -> f64 {
+        self.playhead.borrow().time
+    }
+
+    /// Jumps to `time` seconds without firing the events in between.
+    pub fn seek(&self, time: f64) {
+        self.playhead.borrow_mut().time = time.clamp(0.0, self.duration);
+    }
+
+    /// Jumps to a share of the duration (0.0..1.0).
+    pub fn seek_progress(&self, progress: f64) {
+        self.seek(progress * self.duration);
+    }
+
+    /// Pauses the timeline and scrubs it with `element`'s scroll position
+    /// from then on, the top being the start and the bottom the end. The
+    /// returned function stops scrubbing; call it when `element` unmounts.
+    pub fn scrub_with_scroll(&self, element: &Element) -> impl FnOnce() {
+        self.pause();
+        let timeline = self.clone();
+        let target = element.clone();
+        let closure = Closure::<dyn FnMut()>::new(move || {
+            let range = (target.scroll_height() - target.client_height()).max(1) as f64;
+            timeline.seek_progress(target.scroll_top() as f64 / range);
+        });
+        element
+            .add_event_listener_with_callback("scroll", closure.as_ref().unchecked_ref())
+            .unwrap();
+        let element = element.clone();
+        move || {
+            element
+                .remove_event_listener_with_callback("scroll", closure.as_ref().unchecked_ref())
+                .unwrap();
+        }
+    }
+
+    /// Moves the playhead `seconds` forward if playing and returns the
+    /// events passed on the way, in order.
+    pub(crate) fn advance(&self, seconds: f64) -> Vec<TimelineEvent> {
+        let mut playhead = self.playhead.borrow_mut();
+        if !playhead.playing || self.duration <= 0.0 {
+            return Vec::new();
+        }
+
+        let from = playhead.time;
+        let mut to = from + seconds;
+        let mut passed = self.events_between(from, to.min(self.duration));
+        if to >= self.duration {
+            if self.looping {
+                to %= self.duration;
+                passed.extend(self.events_between(0.0, to));
+            } else {
+                to = self.duration;
+                playhead.playing = false;
+                // Events right at the end still fire
+                passed.extend(self.events.iter().filter(|(time, _)| *time == self.duration).map(|(_, event)| event.clone()));
+            }
+        }
+        playhead.time = to;
+        passed
+    }
+
+    // Events in from..to
+    fn events_between(&self, from: f64, to: f64) -> Vec<TimelineEvent> {
+        self.events
+            .iter()
+            .filter(|(time, _)| *time >= from && *time < to)
+            .map(|(_, event)| event.clone())
+            .collect()
+    }
+
+    /// The effect of the last cut at or before `time`.
+    pub fn effect_at(&self, time: f64) -> Option<StarEffect> {
+        self.events
+            .iter()
+            .take_while(|(at, _)| *at <= time)
+            .filter_map(|(_, event)| match event {
+                TimelineEvent::Cut(effect) => Some(*effect),
+                _ => None,
+            })
+            .last()
+    }
+
+    /// `param`'s value at `time`, or `None` if it has no track.
+    pub fn value_at(&self, param: Param, time: f64) -> Option<f64> {
+        let (_, keyframes) = self.tracks.iter().find(|(track, _)| *track == param)?;
+        let next = keyframes.partition_point(|keyframe| keyframe.time <= time);
+        if next == 0 {
+            return keyframes.first().map(|keyframe| keyframe.value);
+        }
+        let previous = &keyframes[next - 1];
+        let Some(next) = keyframes.get(next) else {
+            return Some(previous.value);
+        };
+
+        let progress = (time - previous.time) / (next.time - previous.time);
+        Some(previous.value + (next.value - previous.value) * next.easing.apply(progress))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tween::Curve;
+
+    #[test]
+    fn value_between_keyframes() {
+        let timeline = Timeline::new(10.0)
+            .keyframe(Param::Speed, 2.0, 1.0, Easing::Linear)
+            .keyframe(Param::Speed, 4.0, 3.0, Easing::Linear)
+            .keyframe(Param::Speed, 6.0, 7.0, Easing::In(Curve::Quad));
+        assert_eq!(timeline.value_at(Param::Speed, 0.0), Some(1.0));
+        assert_eq!(timeline.value_at(Param::Speed, 3.0), Some(2.0));
+        // A quarter of the way along an ease-in quad at the midpoint
+        assert_eq!(timeline.value_at(Param::Speed, 5.0), Some(4.0));
+        assert_eq!(timeline.value_at(Param::Speed, 9.0), Some(7.0));
+        assert_eq!(timeline.value_at(Param::Density, 3.0), None);
+    }
+
+    #[test]
+    fn keyframes_at_the_same_time_jump() {
+        let timeline = Timeline::new(4.0)
+            .keyframe(Param::Density, 0.0, 0.0, Easing::Linear)
+            .keyframe(Param::Density, 2.0, 0.5, Easing::Linear)
+            .keyframe(Param::Density, 2.0, 1.0, Easing::Linear);
+        assert_eq!(timeline.value_at(Param::Density, 1.0), Some(0.25));
+        assert_eq!(timeline.value_at(Param::Density, 2.0), Some(1.0));
+    }
+
+    #[test]
+    fn cuts_hold_until_the_next() {
+        let timeline = Timeline::new(6.0)
+            .event(0.0, TimelineEvent::Cut(StarEffect::Twinkle))
+            .event(1.0, TimelineEvent::ShootingStars(3))
+            .event(3.0, TimelineEvent::Cut(StarEffect::Warp));
+        assert_eq!(timeline.effect_at(2.9), Some(StarEffect::Twinkle));
+        assert_eq!(timeline.effect_at(3.0), Some(StarEffect::Warp));
+        assert_eq!(timeline.effect_at(6.0), Some(StarEffect::Warp));
+        assert_eq!(Timeline::new(1.0).effect_at(0.5), None);
+    }
+
+    #[test]
+    fn advance_fires_passed_events_in_order() {
+        let timeline = Timeline::new(4.0)
+            .event(2.0, TimelineEvent::Marker("b".to_string()))
+            .event(1.0, TimelineEvent::Marker("a".to_string()))
+            .event(4.0, TimelineEvent::Marker("end".to_string()));
+        assert_eq!(
+            timeline.advance(2.5),
+            vec![TimelineEvent::Marker("a".to_string()), TimelineEvent::Marker("b".to_string())]
+        );
+        assert_eq!(timeline.advance(2.0), vec![TimelineEvent::Marker("end".to_string())]);
+        assert!(!timeline.is_playing());
+        assert_eq!(timeline.time(), 4.0);
+
+        // Playing a finished timeline starts it over
+        timeline.play();
+        assert_eq!(timeline.time(), 0.0);
+    }
+
+    #[test]
+    fn looping_wraps_and_fires_across_the_end() {
+        let timeline = Timeline::new(2.0).looped().event(0.5, TimelineEvent::ShootingStars(1));
+        assert!(timeline.advance(1.0) == vec![TimelineEvent::ShootingStars(1)]);
+        assert!(timeline.advance(1.75) == vec![TimelineEvent::ShootingStars(1)]);
+        assert!((timeline.time() - 0.75).abs() < 1e-9);
+        assert!(timeline.is_playing());
+    }
+
+    #[test]
+    fn seeking_and_pausing_fire_nothing() {
+        let timeline = Timeline::new(4.0).event(1.0, TimelineEvent::ShootingStars(1));
+        timeline.seek(3.0);
+        assert!(timeline.advance(0.5).is_empty());
+        timeline.pause();
+        assert!(timeline.advance(1.0).is_empty());
+        assert_eq!(timeline.time(), 3.5);
+    }
+}
@@ -0,0 +1,48 @@
+use crate::canvas::{context_2d, create_canvas};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+/// Persistent layer the effect draws into instead of the visible canvas.
+/// Every frame the previous contents fade towards transparent rather than
+/// being cleared, so moving stars leave real motion trails. Fading to
+/// transparent (instead of painting the background over it) keeps layers
+/// underneath, like the nebula, from being smeared along with the stars.
+pub struct Trails {
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    width: f64,
+    height: f64,
+    decay: f64,
+}
+
+impl Trails {
+    /// `decay` is the share of brightness a pixel keeps per 60 Hz frame.
+    pub fn new(decay: f64, width: f64, height: f64) -> Self {
+        let canvas = create_canvas(width as u32, height as u32);
+        let context = context_2d(&canvas);
+        Trails {
+            canvas,
+            context,
+            width,
+            height,
+            decay: decay.clamp(0.0, 0.98),
+        }
+    }
+
+    pub fn context(&self) -> &CanvasRenderingContext2d {
+        &self.context
+    }
+
+    pub fn fade(&self, dt: f64) {
+        // With 8-bit channels a very small fade never reaches zero, so keep
+        // a floor that still clears old trails eventually
+        let fade = (1.0 - self.decay.powf(dt)).max(0.02);
+        self.context.set_global_composite_operation("destination-out").unwrap();
+        self.context.set_fill_style_str(&format!("rgba(0, 0, 0, {})", fade));
+        self.context.fill_rect(0.0, 0.0, self.width, self.height);
+        self.context.set_global_composite_operation("source-over").unwrap();
+    }
+
+    pub fn draw(&self, context: &CanvasRenderingContext2d) {
+        context.draw_image_with_html_canvas_element(&self.canvas, 0.0, 0.0).unwrap();
+    }
+}
@@ -0,0 +1,433 @@
+//! Interpolation for effect parameters and colors: the standard Penner
+//! easing curves, CSS-style cubic Béziers, tweens and springs.
+//!
+//! Tweens and springs advance by the same `dt` as `Effect::update`, the
+//! time since the last frame in 60 Hz frames, and keep their timing on
+//! any display rate.
+
+use std::f64::consts::PI;
+
+/// Shape of an easing curve, as it accelerates out of the start.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    Quad,
+    Cubic,
+    Quart,
+    Quint,
+    Sine,
+    Expo,
+    Circ,
+    /// Pulls back a little before setting off.
+    Back,
+    Elastic,
+    Bounce,
+}
+
+impl Curve {
+    // The ease-in form; out and in-out are derived from it
+    fn ease_in(self, t: f64) -> f64 {
+        match self {
+            Curve::Quad => t * t,
+            Curve::Cubic => t * t * t,
+            Curve::Quart => t.powi(4),
+            Curve::Quint => t.powi(5),
+            Curve::Sine => 1.0 - (t * PI / 2.0).cos(),
+            Curve::Expo => {
+                if t == 0.0 {
+                    0.0
+                } else {
+                    2f64.powf(10.0 * t - 10.0)
+                }
+            }
+            Curve::Circ => 1.0 - (1.0 - t * t).max(0.0).sqrt(),
+            Curve::Back => {
+                let overshoot = 1.70158;
+                (overshoot + 1.0) * t * t * t - overshoot * t * t
+            }
+            Curve::Elastic => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    -(2f64.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * PI * 2.0 / 3.0).sin()
+                }
+            }
+            Curve::Bounce => 1.0 - bounce_out(1.0 - t),
+        }
+    }
+}
+
+fn bounce_out(t: f64) -> f64 {
+    let (n, d) = (7.5625, 2.75);
+    if t < 1.0 / d {
+        n * t * t
+    } else if t < 2.0 / d {
+        let t = t - 1.5 / d;
+        n * t * t + 0.75
+    } else if t < 2.5 / d {
+        let t = t - 2.25 / d;
+        n * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / d;
+        n * t * t + 0.984375
+    }
+}
+
+/// Maps progress in time to progress in value. `Back`, `Elastic` and some
+/// Béziers overshoot outside 0.0..1.0 on the way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    /// Holds the start value, then jumps at the end.
+    Step,
+    In(Curve),
+    Out(Curve),
+    InOut(Curve),
+    /// CSS `cubic-bezier(x1, y1, x2, y2)`.
+    CubicBezier(f64, f64, f64, f64),
+}
+
+impl Easing {
+    pub const EASE: Easing = Easing::CubicBezier(0.25, 0.1, 0.25, 1.0);
+    pub const EASE_IN: Easing = Easing::CubicBezier(0.42, 0.0, 1.0, 1.0);
+    pub const EASE_OUT: Easing = Easing::CubicBezier(0.0, 0.0, 0.58, 1.0);
+    pub const EASE_IN_OUT: Easing = Easing::CubicBezier(0.42, 0.0, 0.58, 1.0);
+
+    /// Eased progress for progress `t` (0.0..1.0).
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::Step => {
+                if t < 1.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+            Easing::In(curve) => curve.ease_in(t),
+            Easing::Out(curve) => 1.0 - curve.ease_in(1.0 - t),
+            Easing::InOut(curve) => {
+                if t < 0.5 {
+                    curve.ease_in(t * 2.0) / 2.0
+                } else {
+                    1.0 - curve.ease_in(2.0 - t * 2.0) / 2.0
+                }
+            }
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                let s = solve_bezier(x1.clamp(0.0, 1.0), x2.clamp(0.0, 1.0), t);
+                bezier(y1, y2, s)
+            }
+        }
+    }
+}
+
+// One coordinate of a Bézier from 0 to 1 with control points a and b
+fn bezier(a: f64, b: f64, s: f64) -> f64 {
+    let u = 1.0 - s;
+    3.0 * u * u * s * a + 3.0 * u * s * s * b + s * s * s
+}
+
+fn bezier_slope(a: f64, b: f64, s: f64) -> f64 {
+    let u = 1.0 - s;
+    3.0 * u * u * a + 6.0 * u * s * (b - a) + 3.0 * s * s * (1.0 - b)
+}
+
+// The curve parameter where x reaches `x`: Newton's method, which usually
+// lands in a few steps, with bisection for the flat spots where it stalls
+fn solve_bezier(x1: f64, x2: f64, x: f64) -> f64 {
+    let mut s = x;
+    for _ in 0..8 {
+        let error = bezier(x1, x2, s) - x;
+        if error.abs() < 1e-6 {
+            return s;
+        }
+        let slope = bezier_slope(x1, x2, s);
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        s = (s - error / slope).clamp(0.0, 1.0);
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    s = x;
+    for _ in 0..30 {
+        if bezier(x1, x2, s) < x {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) / 2.0;
+    }
+    s
+}
+
+/// Values that can be blended.
+pub trait Lerp: Clone {
+    /// `self` at `t` = 0.0, `to` at `t` = 1.0. Overshooting `t` extrapolates.
+    fn lerp(&self, to: &Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(&self, to: &Self, t: f64) -> Self {
+        self + (to - self) * t
+    }
+}
+
+/// RGB colors, blended in linear light so midpoints don't go muddy.
+impl Lerp for (u8, u8, u8) {
+    fn lerp(&self, to: &Self, t: f64) -> Self {
+        let channel = |from: u8, to: u8| {
+            let (from, to) = ((from as f64 / 255.0).powf(2.2), (to as f64 / 255.0).powf(2.2));
+            let blended = (from + (to - from) * t).clamp(0.0, 1.0);
+            (blended.powf(1.0 / 2.2) * 255.0).round() as u8
+        };
+        (channel(self.0, to.0), channel(self.1, to.1), channel(self.2, to.2))
+    }
+}
+
+/// How a `Starfield` setting moves to a new value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    /// Length in seconds.
+    pub duration: f64,
+    pub easing: Easing,
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Transition {
+            duration: 1.0,
+            easing: Easing::InOut(Curve::Cubic),
+        }
+    }
+}
+
+/// A value moving from one point to another over a fixed time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tween<T: Lerp> {
+    from: T,
+    to: T,
+    // in 60 Hz frames
+    duration: f64,
+    elapsed: f64,
+    easing: Easing,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// `duration` is in seconds.
+    pub fn new(from: T, to: T, duration: f64, easing: Easing) -> Self {
+        Tween {
+            from,
+            to,
+            duration: duration.max(0.0) * 60.0,
+            elapsed: 0.0,
+            easing,
+        }
+    }
+
+    /// A tween that's already at `value`.
+    pub fn still(value: T) -> Self {
+        Tween::new(value.clone(), value, 0.0, Easing::Linear)
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+    }
+
+    pub fn value(&self) -> T {
+        if self.is_finished() {
+            return self.to.clone();
+        }
+        self.from.lerp(&self.to, self.easing.apply(self.elapsed / self.duration))
+    }
+
+    pub fn target(&self) -> &T {
+        &self.to
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Heads for `to` from wherever the tween is now, or jumps straight
+    /// there without a transition.
+    pub fn retarget(&mut self, to: T, transition: Option<Transition>) {
+        *self = match transition {
+            Some(transition) => Tween::new(self.value(), to, transition.duration, transition.easing),
+            None => Tween::still(to),
+        };
+    }
+}
+
+/// A damped spring chasing a target. Unlike a tween it keeps its velocity
+/// when the target moves, so it suits values that change all the time, like
+/// ones following the pointer or the scroll position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spring {
+    /// Pull towards the target; higher is snappier.
+    pub stiffness: f64,
+    /// Resistance to motion; 2 * sqrt(stiffness) settles fastest without
+    /// overshooting.
+    pub damping: f64,
+    pub target: f64,
+    value: f64,
+    velocity: f64,
+}
+
+impl Spring {
+    pub fn new(value: f64, stiffness: f64, damping: f64) -> Self {
+        Spring {
+            stiffness,
+            damping,
+            target: value,
+            value,
+            velocity: 0.0,
+        }
+    }
+
+    /// A spring that settles without overshooting.
+    pub fn critically_damped(value: f64, stiffness: f64) -> Self {
+        Spring::new(value, stiffness, 2.0 * stiffness.sqrt())
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        // Fixed substeps in seconds, so a long frame doesn't blow it up
+        let steps = (dt * 4.0).ceil().max(1.0);
+        let h = dt / 60.0 / steps;
+        for _ in 0..steps as usize {
+            let force = self.stiffness * (self.target - self.value) - self.damping * self.velocity;
+            self.velocity += force * h;
+            self.value += self.velocity * h;
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn is_settled(&self) -> bool {
+        (self.target - self.value).abs() < 1e-3 && self.velocity.abs() < 1e-3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [Curve; 10] = [
+        Curve::Quad,
+        Curve::Cubic,
+        Curve::Quart,
+        Curve::Quint,
+        Curve::Sine,
+        Curve::Expo,
+        Curve::Circ,
+        Curve::Back,
+        Curve::Elastic,
+        Curve::Bounce,
+    ];
+
+    fn easings() -> Vec<Easing> {
+        let mut easings = vec![
+            Easing::Linear,
+            Easing::Step,
+            Easing::EASE,
+            Easing::EASE_IN,
+            Easing::EASE_OUT,
+            Easing::EASE_IN_OUT,
+        ];
+        for curve in CURVES {
+            easings.extend([Easing::In(curve), Easing::Out(curve), Easing::InOut(curve)]);
+        }
+        easings
+    }
+
+    #[test]
+    fn easings_start_at_0_and_end_at_1() {
+        for easing in easings() {
+            assert!(easing.apply(0.0).abs() < 1e-6, "{:?} at 0", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{:?} at 1", easing);
+        }
+    }
+
+    #[test]
+    fn easings_clamp_progress() {
+        for easing in easings() {
+            assert_eq!(easing.apply(-0.5), easing.apply(0.0), "{:?}", easing);
+            assert_eq!(easing.apply(1.5), easing.apply(1.0), "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn css_beziers_are_monotonic() {
+        for easing in [Easing::EASE, Easing::EASE_IN, Easing::EASE_OUT, Easing::EASE_IN_OUT] {
+            let mut previous = 0.0;
+            for i in 0..=200 {
+                let value = easing.apply(i as f64 / 200.0);
+                assert!(value >= previous - 1e-9, "{:?} at {}", easing, i);
+                previous = value;
+            }
+        }
+    }
+
+    #[test]
+    fn bezier_solver_inverts_x() {
+        for (x1, x2) in [(0.25, 0.25), (0.42, 1.0), (0.0, 0.58), (0.9, 0.1), (1.0, 0.0)] {
+            for i in 0..=20 {
+                let x = i as f64 / 20.0;
+                let s = solve_bezier(x1, x2, x);
+                assert!((bezier(x1, x2, s) - x).abs() < 1e-5, "({}, {}) at {}", x1, x2, x);
+            }
+        }
+    }
+
+    #[test]
+    fn straight_bezier_is_linear() {
+        let easing = Easing::CubicBezier(0.0, 0.0, 1.0, 1.0);
+        for i in 0..=10 {
+            let t = i as f64 / 10.0;
+            assert!((easing.apply(t) - t).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn tween_runs_over_its_duration() {
+        let mut tween = Tween::new(0.0, 10.0, 1.0, Easing::Linear);
+        tween.update(30.0);
+        assert!((tween.value() - 5.0).abs() < 1e-9);
+        assert!(!tween.is_finished());
+        tween.update(45.0);
+        assert!(tween.is_finished());
+        assert_eq!(tween.value(), 10.0);
+    }
+
+    #[test]
+    fn retarget_starts_from_the_current_value() {
+        let mut tween = Tween::new(0.0, 10.0, 1.0, Easing::Linear);
+        tween.update(30.0);
+        tween.retarget(0.0, Some(Transition::default()));
+        assert!((tween.value() - 5.0).abs() < 1e-9);
+        assert_eq!(*tween.target(), 0.0);
+
+        tween.retarget(3.0, None);
+        assert_eq!(tween.value(), 3.0);
+    }
+
+    #[test]
+    fn critically_damped_spring_settles_without_overshoot() {
+        let mut spring = Spring::critically_damped(0.0, 120.0);
+        spring.target = 1.0;
+        for _ in 0..600 {
+            spring.update(1.0);
+            assert!(spring.value() <= 1.0 + 1e-6);
+        }
+        assert!(spring.is_settled());
+    }
+
+    #[test]
+    fn color_lerp_hits_both_ends() {
+        let (from, to) = ((255, 0, 0), (0, 0, 255));
+        assert_eq!(from.lerp(&to, 0.0), from);
+        assert_eq!(from.lerp(&to, 1.0), to);
+    }
+}
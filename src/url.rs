@@ -0,0 +1,172 @@
+//! Starfield settings carried in the page URL, so a demo can be shared as a
+//! link: `?effect=spiral&seed=42&speed=1.5&palette=ember&density=0.5`.
+//! The same keys work in the hash (`#effect=spiral`), which wins over the
+//! query when both are given. `?screensaver` opens the screensaver, and
+//! `?screensaver=0` or `=false` doesn't.
+
+use crate::nebula::NebulaConfig;
+use crate::starfield::StarEffect;
+use wasm_bindgen::JsValue;
+use web_sys::UrlSearchParams;
+
+// Accepted ranges; values outside them are clamped
+const SPEED: (f64, f64) = (0.1, 5.0);
+const DENSITY: (f64, f64) = (0.05, 1.0);
+
+/// Settings read from the URL. Missing or invalid keys are `None`, so the
+/// page's own settings apply.
+#[derive(Clone, Default, PartialEq)]
+pub struct UrlConfig {
+    pub effect: Option<StarEffect>,
+    pub seed: Option<u64>,
+    pub speed: Option<f64>,
+    /// Nebula palette name, see `NebulaConfig::palette`.
+    pub palette: Option<String>,
+    pub density: Option<f64>,
+    pub screensaver: bool,
+}
+
+impl UrlConfig {
+    /// Reads the URL of the current page.
+    pub fn from_location() -> Self {
+        let location = web_sys::window().unwrap().location();
+        let query = UrlConfig::parse(&location.search().unwrap_or_default());
+        let hash = UrlConfig::parse(&location.hash().unwrap_or_default());
+        UrlConfig {
+            effect: hash.effect.or(query.effect),
+            seed: hash.seed.or(query.seed),
+            speed: hash.speed.or(query.speed),
+            palette: hash.palette.or(query.palette),
+            density: hash.density.or(query.density),
+            screensaver: hash.screensaver || query.screensaver,
+        }
+    }
+
+    /// Parses `key=value` pairs, with or without a leading `?` or `#`.
+    /// Unknown keys are ignored; bad values are skipped with a console warning.
+    pub fn parse(query: &str) -> Self {
+        let params = UrlSearchParams::new_with_str(query.trim_start_matches(['?', '#'])).unwrap();
+        let get = |key: &str| params.get(key).filter(|value| !value.is_empty());
+
+        let effect = get("effect").and_then(|name| {
+            let effect = StarEffect::from_name(&name.to_lowercase());
+            if effect.is_none() {
+                warn("effect", &name);
+            }
+            effect
+        });
+        let seed = get("seed").and_then(|seed| match seed.parse() {
+            Ok(seed) => Some(seed),
+            Err(_) => {
+                warn("seed", &seed);
+                None
+            }
+        });
+        let palette = get("palette").map(|name| name.to_lowercase()).and_then(|name| {
+            if NebulaConfig::palette(&name).is_none() {
+                warn("palette", &name);
+                return None;
+            }
+            Some(name)
+        });
+
+        UrlConfig {
+            effect,
+            seed,
+            speed: get("speed").and_then(|speed| number("speed", &speed, SPEED)),
+            palette,
+            density: get("density").and_then(|density| number("density", &density, DENSITY)),
+            screensaver: params.get("screensaver").is_some_and(|value| flag("screensaver", &value)),
+        }
+    }
+
+    /// The settings as a query string, starting with `?`, or empty if none
+    /// are set.
+    pub fn to_query(&self) -> String {
+        let params = UrlSearchParams::new().unwrap();
+        if let Some(effect) = self.effect {
+            params.append("effect", effect.name());
+        }
+        if let Some(seed) = self.seed {
+            params.append("seed", &seed.to_string());
+        }
+        if let Some(speed) = self.speed {
+            params.append("speed", &speed.to_string());
+        }
+        if let Some(palette) = &self.palette {
+            params.append("palette", palette);
+        }
+        if let Some(density) = self.density {
+            params.append("density", &density.to_string());
+        }
+        if self.screensaver {
+            params.append("screensaver", "");
+        }
+
+        let query = String::from(params.to_string());
+        if query.is_empty() {
+            query
+        } else {
+            format!("?{}", query)
+        }
+    }
+
+    /// Replaces the page URL with these settings, without adding a history
+    /// entry. Any hash is dropped, as it would override the new query.
+    pub fn replace_location(&self) {
+        let window = web_sys::window().unwrap();
+        let path = window.location().pathname().unwrap_or_default();
+        let url = format!("{}{}", path, self.to_query());
+        window
+            .history()
+            .unwrap()
+            .replace_state_with_url(&JsValue::NULL, "", Some(&url))
+            .unwrap();
+    }
+
+    /// The nebula for the palette, if one is set.
+    pub fn nebula(&self) -> Option<NebulaConfig> {
+        self.palette.as_deref().and_then(NebulaConfig::palette)
+    }
+}
+
+fn number(key: &str, value: &str, (min, max): (f64, f64)) -> Option<f64> {
+    match value.parse::<f64>() {
+        Ok(number) if number.is_finite() => Some(number.clamp(min, max)),
+        _ => {
+            warn(key, value);
+            None
+        }
+    }
+}
+
+// A bare key or an empty value turns a switch on, like `?screensaver`
+fn flag(key: &str, value: &str) -> bool {
+    match value.to_lowercase().as_str() {
+        "" | "1" | "true" | "on" => true,
+        "0" | "false" | "off" => false,
+        _ => {
+            warn(key, value);
+            false
+        }
+    }
+}
+
+fn warn(key: &str, value: &str) {
+    let message = format!("hyperspace: ignoring invalid URL setting {}={}", key, value);
+    web_sys::console::warn_1(&message.into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flag_values() {
+        assert!(flag("screensaver", ""));
+        assert!(flag("screensaver", "1"));
+        assert!(flag("screensaver", "True"));
+        assert!(!flag("screensaver", "0"));
+        assert!(!flag("screensaver", "false"));
+    }
+}